@@ -1,6 +1,12 @@
 extern crate objectbox;
 
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime};
+
+use objectbox::chrono::{DateTime, NaiveDateTime, Utc};
 use objectbox::macros::entity;
+use objectbox::relations::{Backlinks, ToMany, ToOne};
+use objectbox::traits::PropertyConverter;
 
 #[derive(Debug)]
 #[entity]
@@ -43,5 +49,173 @@ pub struct Entity {
     pub t_char: char,
     pub t_vec_string: Vec<String>,
     pub t_vec_bytes: Vec<u8>,
-    // transient: Option<bool> // not yet supported
-}
\ No newline at end of file
+}
+
+#[derive(Debug)]
+#[entity]
+pub struct Customer {
+    #[id]
+    pub id: u64,
+    pub name: String,
+    #[backlink(to = "customer")]
+    pub orders: Backlinks<Order>,
+}
+
+#[derive(Debug)]
+#[entity]
+pub struct Order {
+    #[id]
+    pub id: u64,
+    pub status: String,
+    pub customer: ToOne<Customer>,
+}
+
+#[derive(Debug)]
+#[entity]
+pub struct Teacher {
+    #[id]
+    pub id: u64,
+    pub name: String,
+    #[backlink]
+    pub students: Backlinks<Student>,
+}
+
+#[derive(Debug)]
+#[entity]
+pub struct Student {
+    #[id]
+    pub id: u64,
+    pub name: String,
+    pub teachers: ToMany<Teacher>,
+}
+
+#[derive(Debug)]
+#[entity]
+pub struct Contact {
+    #[id]
+    pub id: u64,
+    #[unique]
+    pub email: String,
+    #[unique(on_conflict = "replace")]
+    pub handle: String,
+}
+
+#[derive(Debug, PartialEq)]
+#[entity]
+pub struct Nullable {
+    #[id]
+    pub id: u64,
+    pub o_bool: Option<bool>,
+    pub o_u8: Option<u8>,
+    pub o_i16: Option<i16>,
+    pub o_u32: Option<u32>,
+    pub o_i64: Option<i64>,
+    pub o_f32: Option<f32>,
+    pub o_f64: Option<f64>,
+    pub o_char: Option<char>,
+    pub o_string: Option<String>,
+    pub o_vec_bytes: Option<Vec<u8>>,
+    pub o_vec_string: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+#[entity]
+pub struct Session {
+    #[id]
+    pub id: u64,
+    pub token: String,
+    #[transient]
+    pub cache: HashMap<String, u32>,
+    #[transient(default = "std::time::Instant::now")]
+    pub loaded_at: Instant,
+}
+
+#[derive(Debug, PartialEq)]
+#[entity]
+pub struct Event {
+    #[id]
+    pub id: u64,
+    pub starts: DateTime<Utc>,
+    #[property(type = "date_nano")]
+    pub logged: DateTime<Utc>,
+    pub local: NaiveDateTime,
+    pub modified: SystemTime,
+    pub ends: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+pub struct PriorityConverter;
+
+impl PropertyConverter for PriorityConverter {
+    type Value = Priority;
+    type Stored = i64;
+
+    fn to_stored(value: &Priority) -> i64 {
+        *value as i64
+    }
+
+    fn from_stored(stored: i64) -> Priority {
+        match stored {
+            2 => Priority::High,
+            1 => Priority::Normal,
+            _ => Priority::Low,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaskKey(pub [u8; 4]);
+
+pub struct TaskKeyConverter;
+
+impl PropertyConverter for TaskKeyConverter {
+    type Value = TaskKey;
+    type Stored = Vec<u8>;
+
+    fn to_stored(value: &TaskKey) -> Vec<u8> {
+        value.0.to_vec()
+    }
+
+    fn from_stored(stored: Vec<u8>) -> TaskKey {
+        TaskKey(stored.try_into().unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag(pub String);
+
+pub struct TagConverter;
+
+impl PropertyConverter for TagConverter {
+    type Value = Tag;
+    type Stored = String;
+
+    fn to_stored(value: &Tag) -> String {
+        value.0.clone()
+    }
+
+    fn from_stored(stored: String) -> Tag {
+        Tag(stored)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[entity]
+pub struct Task {
+    #[id]
+    pub id: u64,
+    #[property(convert = "PriorityConverter")]
+    pub priority: Priority,
+    #[property(convert = "TaskKeyConverter", type = "bytes")]
+    pub key: TaskKey,
+    #[property(convert = "TagConverter", type = "string")]
+    pub tag: Tag,
+    #[property(convert = "PriorityConverter")]
+    pub escalated: Option<Priority>,
+}
@@ -0,0 +1,66 @@
+use example::{make_factory_map, make_model, Entity3};
+use objectbox::{error, opt::Opt, store::Store};
+
+use serial_test::serial;
+
+mod common;
+use common::new_entity3;
+
+#[test]
+#[serial]
+fn async_box_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+
+    let mut a = new_entity3("a");
+    let mut b = new_entity3("b");
+    let mut c = new_entity3("c");
+
+    // the ids are assigned when enqueued
+    {
+        let mut async_box = box3.async_ops()?;
+        let id_a = async_box.put(&mut a)?;
+        assert_ne!(0, id_a);
+        assert_eq!(id_a, a.id);
+        async_box.insert(&mut b)?;
+        assert_ne!(0, b.id);
+
+        // only stored objects can be updated
+        assert!(async_box.update(&mut c).is_err());
+        assert_eq!(0, c.id);
+    }
+    assert!(store.await_async_completion());
+    assert_eq!(2, box3.count()?);
+    assert_eq!("a", box3.get(a.id)?.unwrap().hello);
+
+    // update and remove, with an own queue
+    {
+        let mut async_box = box3.async_ops_with_timeout(1000)?;
+        b.hello = "bb".to_string();
+        async_box.update(&mut b)?;
+        async_box.remove(a.id)?;
+    }
+    assert!(store.await_async_completion());
+    assert_eq!(1, box3.count()?);
+    assert!(box3.get(a.id)?.is_none());
+    assert_eq!("bb", box3.get(b.id)?.unwrap().hello);
+
+    // a failed insert doesn't overwrite the existing object
+    {
+        let mut async_box = box3.async_ops()?;
+        let mut copy = Entity3 {
+            id: b.id,
+            hello: "copy".to_string(),
+        };
+        async_box.insert(&mut copy)?;
+    }
+    assert!(store.await_async_completion());
+    assert_eq!("bb", box3.get(b.id)?.unwrap().hello);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use example::{make_factory_map, make_model, Entity3};
+use objectbox::{
+    error,
+    opt::Opt,
+    store::{Store, WeakStore},
+};
+
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn attach_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let path = PathBuf::from(opt.get_directory());
+    let store = Store::new(opt, make_factory_map())?;
+    assert!(Store::is_open(&path));
+
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+    box3.put(&mut Entity3 {
+        id: 0,
+        hello: "host".to_string(),
+    })?;
+
+    // by path and by id, with their own factory maps
+    {
+        let by_path = Store::attach(&path, make_factory_map())?;
+        assert_eq!(store.id(), by_path.id());
+        assert_eq!(1, by_path.get_box::<Entity3>()?.count()?);
+
+        let by_id = Store::attach_id(store.id(), make_factory_map())?;
+        by_id.get_box::<Entity3>()?.put(&mut Entity3 {
+            id: 0,
+            hello: "plugin".to_string(),
+        })?;
+    }
+    // closing the attached instances keeps the store open
+    assert!(Store::is_open(&path));
+    assert_eq!(2, box3.count()?);
+
+    // attached, when already open
+    {
+        let mut model = make_model();
+        let opt = Opt::from_model(&mut model)?;
+        let (attached, was_attached) = Store::attach_or_open(opt, make_factory_map(), false)?;
+        assert!(was_attached);
+        assert_eq!(store.id(), attached.id());
+    }
+
+    // a weak store doesn't keep the store open
+    let weak = store.weak()?;
+    let weak_by_id = WeakStore::by_id(store.id())?;
+    {
+        let upgraded = weak.upgrade(make_factory_map())?;
+        assert_eq!(2, upgraded.get_box::<Entity3>()?.count()?);
+        assert_eq!(store.id(), weak_by_id.upgrade(make_factory_map())?.id());
+    }
+    let store_id = store.id();
+    drop(box3);
+    drop(store);
+    assert!(!Store::is_open(&path));
+    assert!(weak.upgrade(make_factory_map()).is_err());
+    assert!(weak_by_id.upgrade(make_factory_map()).is_err());
+
+    // nothing to attach to
+    assert!(Store::attach(&path, make_factory_map()).is_err());
+    assert!(Store::attach_id(store_id, make_factory_map()).is_err());
+
+    // opened, when closed
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let (store, was_attached) = Store::attach_or_open(opt, make_factory_map(), false)?;
+    assert!(!was_attached);
+    assert_eq!(2, store.get_box::<Entity3>()?.count()?);
+
+    Ok(())
+}
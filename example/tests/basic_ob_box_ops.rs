@@ -2,7 +2,7 @@ use example::{make_factory_map, make_model, Entity, Entity2, Entity3};
 use objectbox::error;
 use objectbox::traits::{self, IdExt};
 use objectbox::{opt::Opt, store::Store};
-use std::rc;
+use std::sync;
 
 use serial_test::serial;
 
@@ -23,15 +23,15 @@ fn test_box_put_and_count_and_remove_all() -> error::Result<()> {
 
     let trait_map2 = make_factory_map();
     let f1 = trait_map2
-        .get::<rc::Rc<dyn traits::EntityFactoryExt<Entity>>>()
+        .get::<sync::Arc<dyn traits::EntityFactoryExt<Entity>>>()
         .unwrap()
         .clone();
     let f2 = trait_map2
-        .get::<rc::Rc<dyn traits::EntityFactoryExt<Entity2>>>()
+        .get::<sync::Arc<dyn traits::EntityFactoryExt<Entity2>>>()
         .unwrap()
         .clone();
     let f3 = trait_map2
-        .get::<rc::Rc<dyn traits::EntityFactoryExt<Entity3>>>()
+        .get::<sync::Arc<dyn traits::EntityFactoryExt<Entity3>>>()
         .unwrap()
         .clone();
 
@@ -0,0 +1,34 @@
+// Every test binary compiles its own copy of this module, and not all of them use every helper.
+#![allow(dead_code)]
+
+use example::{Entity, Entity3};
+
+pub fn new_entity3(hello: &str) -> Entity3 {
+    Entity3 {
+        id: 0,
+        hello: hello.to_string(),
+    }
+}
+
+pub fn new_entity(t_i64: i64, t_f64: f64, t_i8: i8) -> Entity {
+    Entity {
+        id: 0,
+        index_u32: 1,
+        t_bool: false,
+        t_u8: 2,
+        t_i8,
+        t_i16: 4,
+        t_u16: 5,
+        unique_i32: t_i64 as i32,
+        t_i32: 7,
+        t_u32: 8,
+        t_u64: 9,
+        t_i64,
+        t_f32: 12.0,
+        t_f64,
+        t_string: "14".to_string(),
+        t_char: 'c',
+        t_vec_string: vec!["str1".to_string()],
+        t_vec_bytes: vec![0x9],
+    }
+}
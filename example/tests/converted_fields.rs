@@ -0,0 +1,95 @@
+use example::{
+    make_factory_map, make_model, new_task_condition_factory, Priority, Tag, Task,
+    TaskConditionFactory, TaskKey,
+};
+use objectbox::{error, opt::Opt, store::Store};
+
+fn new_task(priority: Priority, key: u8, tag: &str) -> Task {
+    Task {
+        id: 0,
+        priority,
+        key: TaskKey([key; 4]),
+        tag: Tag(tag.to_string()),
+        escalated: None,
+    }
+}
+
+#[test]
+fn converted_fields_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Task>()?;
+
+    let mut task = new_task(Priority::High, 7, "home");
+    task.escalated = Some(Priority::Normal);
+    let id = box1.put(&mut task)?;
+    assert_eq!(Some(task), box1.get(id)?);
+
+    let id = box1.put(&mut new_task(Priority::Low, 1, "work"))?;
+    let read = box1.get(id)?.expect("an object");
+    assert_eq!(Priority::Low, read.priority);
+    assert_eq!(None, read.escalated);
+
+    Ok(())
+}
+
+#[test]
+fn converted_query_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Task>()?;
+
+    box1.put(&mut new_task(Priority::Low, 1, "home"))?;
+    box1.put(&mut new_task(Priority::Normal, 2, "work"))?;
+    box1.put(&mut new_task(Priority::High, 3, "work"))?;
+
+    // the conditions take the field types
+    let TaskConditionFactory {
+        priority, key, tag, ..
+    } = new_task_condition_factory();
+
+    let found = box1.query(&mut priority.eq(Priority::High))?.find()?;
+    assert_eq!(1, found.len());
+    assert_eq!(TaskKey([3; 4]), found[0].key);
+
+    assert_eq!(2, box1.query(&mut priority.ne(Priority::Low))?.count()?);
+    assert_eq!(
+        2,
+        box1.query(&mut priority.member_of(vec![Priority::Low, Priority::High]))?
+            .count()?
+    );
+    assert_eq!(
+        1,
+        box1.query(&mut priority.not_member_of(vec![Priority::Low, Priority::High]))?
+            .count()?
+    );
+
+    assert_eq!(1, box1.query(&mut key.eq(TaskKey([2; 4])))?.count()?);
+    assert_eq!(2, box1.query(&mut key.ne(TaskKey([2; 4])))?.count()?);
+    assert_eq!(
+        2,
+        box1.query(&mut key.member_of(vec![TaskKey([1; 4]), TaskKey([3; 4])]))?
+            .count()?
+    );
+
+    assert_eq!(
+        2,
+        box1.query(&mut tag.eq(Tag("work".to_string())))?.count()?
+    );
+    assert_eq!(
+        1,
+        box1.query(&mut tag.not_member_of(vec![Tag("work".to_string())]))?
+            .count()?
+    );
+
+    // no values: nothing is a member, everything is not
+    assert_eq!(0, box1.query(&mut tag.member_of(vec![]))?.count()?);
+    assert_eq!(0, box1.query(&mut key.member_of(vec![]))?.count()?);
+    assert_eq!(0, box1.query(&mut priority.member_of(vec![]))?.count()?);
+    assert_eq!(3, box1.query(&mut tag.not_member_of(vec![]))?.count()?);
+    assert_eq!(3, box1.query(&mut key.not_member_of(vec![]))?.count()?);
+
+    Ok(())
+}
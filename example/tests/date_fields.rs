@@ -0,0 +1,85 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use example::{
+    make_factory_map, make_model, new_event_condition_factory, Event, EventConditionFactory,
+};
+use objectbox::chrono::{DateTime, Utc};
+use objectbox::{error, opt::Opt, store::Store};
+
+fn new_event(starts_millis: i64) -> Event {
+    let starts = DateTime::from_timestamp_millis(starts_millis).unwrap();
+    Event {
+        id: 0,
+        starts,
+        logged: starts + Duration::from_nanos(123_456),
+        local: starts.naive_utc(),
+        modified: UNIX_EPOCH + Duration::from_millis(starts_millis as u64),
+        ends: None,
+    }
+}
+
+#[test]
+fn date_fields_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Event>()?;
+
+    // Date keeps milliseconds, DateNano nanoseconds
+    let mut event = new_event(1_700_000_000_123);
+    event.logged += Duration::from_nanos(789);
+    event.ends = Some(event.starts + Duration::from_secs(3600));
+    let id = box1.put(&mut event)?;
+    assert_eq!(Some(event), box1.get(id)?);
+
+    let mut precise = new_event(1_700_000_000_000);
+    precise.starts += Duration::from_nanos(999_999);
+    precise.modified = SystemTime::now();
+    let id = box1.put(&mut precise)?;
+    let read = box1.get(id)?.expect("an object");
+    assert_eq!(1_700_000_000_000, read.starts.timestamp_millis());
+    assert_eq!(0, read.starts.timestamp_subsec_nanos() % 1_000_000);
+    assert_eq!(precise.logged, read.logged);
+    assert_eq!(None, read.ends);
+
+    Ok(())
+}
+
+#[test]
+fn date_query_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Event>()?;
+
+    for millis in [1_000, 2_000, 3_000] {
+        box1.put(&mut new_event(millis))?;
+    }
+
+    let at = |millis| DateTime::<Utc>::from_timestamp_millis(millis).unwrap();
+    let EventConditionFactory { starts, logged, .. } = new_event_condition_factory();
+
+    assert_eq!(1, box1.query(&mut starts.before(&at(2_000)))?.count()?);
+    assert_eq!(1, box1.query(&mut starts.after(&at(2_000)))?.count()?);
+    assert_eq!(
+        2,
+        box1.query(&mut starts.between(&at(1_500), &at(3_000)))?
+            .count()?
+    );
+
+    // in nanoseconds, the logged dates are 123456 ns after the start
+    assert_eq!(2, box1.query(&mut logged.after(&at(2_000)))?.count()?);
+    assert_eq!(
+        1,
+        box1.query(&mut logged.between(&at(1_000), &at(1_001)))?
+            .count()?
+    );
+
+    // any TimeValue
+    let naive = at(2_000).naive_utc();
+    assert_eq!(1, box1.query(&mut starts.after(&naive))?.count()?);
+    let system_time: SystemTime = at(2_000).into();
+    assert_eq!(1, box1.query(&mut starts.before(&system_time))?.count()?);
+
+    Ok(())
+}
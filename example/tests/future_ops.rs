@@ -0,0 +1,143 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use example::{
+    make_factory_map, make_model, new_entity3_condition_factory, Entity3, Entity3ConditionFactory,
+};
+use objectbox::{error, future::WorkerPool, opt::Opt, store::Store};
+
+use serial_test::serial;
+
+mod common;
+use common::new_entity3;
+
+// a minimal executor, polls on the current thread, parks until woken
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+#[serial]
+fn future_box_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    assert!(WorkerPool::new(&store, 0).is_err());
+    let pool = WorkerPool::new(&store, 2)?;
+    let box3 = pool.get_box::<Entity3>()?;
+    block_on(box3.remove_all())?;
+
+    // the objects come back with their ids
+    let a = block_on(box3.put(new_entity3("a")))?;
+    assert_ne!(0, a.id);
+    let bc = block_on(box3.put_many(vec![new_entity3("b"), new_entity3("c")]))?;
+    assert!(bc.iter().all(|e| e.id != 0));
+
+    // several operations in flight
+    {
+        let count = box3.count();
+        let get = box3.get(a.id);
+        let contains = box3.contains(bc[0].id);
+        let get_many = box3.get_many(vec![a.id, bc[1].id]);
+        assert_eq!(3, block_on(count)?);
+        assert_eq!("a", block_on(get)?.unwrap().hello);
+        assert!(block_on(contains)?);
+        let names: Vec<String> = block_on(get_many)?
+            .into_iter()
+            .map(|e| e.unwrap().hello)
+            .collect();
+        assert_eq!(vec!["a", "c"], names);
+    }
+
+    assert!(block_on(box3.remove_with_id(a.id))?);
+    assert!(!block_on(box3.contains(a.id))?);
+    assert_eq!(2, block_on(box3.get_all())?.len());
+
+    // the pool finishes the started operations when dropped
+    let count = box3.count();
+    drop(pool);
+    assert_eq!(2, block_on(count)?);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn future_query_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+    box3.put_many(vec![
+        &mut new_entity3("hello world"),
+        &mut new_entity3("brave new world"),
+        &mut new_entity3("bye"),
+    ])?;
+
+    let Entity3ConditionFactory { hello, .. } = new_entity3_condition_factory();
+    let query = box3.query(&mut hello.contains("world"))?;
+
+    let pool = WorkerPool::new(&store, 1)?;
+    let future_query = pool.query(&query)?;
+
+    let mut names: Vec<String> = block_on(future_query.find())?
+        .into_iter()
+        .map(|e| e.hello)
+        .collect();
+    names.sort();
+    assert_eq!(vec!["brave new world", "hello world"], names);
+    assert_eq!(2, block_on(future_query.find_ids())?.len());
+    assert_eq!(2, block_on(future_query.count())?);
+
+    assert_eq!(2, block_on(future_query.remove())?);
+    assert_eq!(1, box3.count()?);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn future_async_box_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+
+    let pool = WorkerPool::new(&store, 1)?;
+    let mut a = new_entity3("a");
+    box3.async_ops()?.put(&mut a)?;
+
+    // awaits the queued write, without blocking the caller
+    assert!(block_on(pool.await_async_submitted())?);
+    assert_eq!("a", box3.get(a.id)?.unwrap().hello);
+    assert!(block_on(pool.await_async_completion())?);
+
+    Ok(())
+}
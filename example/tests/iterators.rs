@@ -0,0 +1,62 @@
+use example::{
+    make_factory_map, make_model, new_entity3_condition_factory, Entity3, Entity3ConditionFactory,
+};
+use objectbox::{error, opt::Opt, store::Store};
+
+mod common;
+use common::new_entity3;
+
+#[test]
+fn iterator_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+
+    assert_eq!(0, box3.iter()?.count());
+
+    let mut objects: Vec<Entity3> = ["a1", "b1", "a2", "b2", "a3"]
+        .iter()
+        .map(|s| new_entity3(s))
+        .collect();
+    box3.put_many(objects.iter_mut().collect())?;
+
+    // the box, in id order
+    let all = box3.iter()?.collect::<error::Result<Vec<Entity3>>>()?;
+    let hellos: Vec<&str> = all.iter().map(|e| e.hello.as_str()).collect();
+    assert_eq!(vec!["a1", "b1", "a2", "b2", "a3"], hellos);
+
+    // stops early, and releases the transaction on drop
+    let first_two: Vec<String> = box3.iter()?.take(2).map(|e| e.unwrap().hello).collect();
+    assert_eq!(vec!["a1", "b1"], first_two);
+    box3.put(&mut new_entity3("b3"))?;
+
+    // the query
+    let Entity3ConditionFactory { hello, .. } = new_entity3_condition_factory();
+    let query = box3.query(&mut hello.starts_with("a"))?;
+    let mut hellos = Vec::new();
+    for object in query.iter()? {
+        hellos.push(object?.hello);
+    }
+    hellos.sort();
+    assert_eq!(vec!["a1", "a2", "a3"], hellos);
+    assert_eq!(query.count()?, query.iter()?.count() as u64);
+
+    // more matches than fit on a page, with the offset and limit of the query
+    let mut many: Vec<Entity3> = (0..150).map(|i| new_entity3(&format!("p{i}"))).collect();
+    box3.put_many(many.iter_mut().collect())?;
+    let query = box3.query(&mut hello.starts_with("p"))?;
+    assert_eq!(150, query.iter()?.count());
+    query.offset_limit(10, 100)?;
+    let paged = query.iter()?.collect::<error::Result<Vec<Entity3>>>()?;
+    assert_eq!(100, paged.len());
+    let found = query.find()?;
+    assert_eq!(
+        found.iter().map(|e| e.id).collect::<Vec<_>>(),
+        paged.iter().map(|e| e.id).collect::<Vec<_>>()
+    );
+    query.limit(0)?;
+    assert_eq!(140, query.iter()?.count());
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use example::{
+    make_factory_map, make_model, new_customer_condition_factory, new_order_condition_factory,
+    new_student_condition_factory, new_teacher_condition_factory, Customer, Order, Student,
+    Teacher,
+};
+use objectbox::{
+    error,
+    opt::Opt,
+    relations::{Backlinks, ToMany, ToOne},
+    store::Store,
+};
+
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn link_query_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut customer_box = store.get_box::<Customer>()?;
+    customer_box.remove_all()?;
+    let mut order_box = store.get_box::<Order>()?;
+    order_box.remove_all()?;
+
+    let new_customer = |name: &str| Customer {
+        id: 0,
+        name: name.to_string(),
+        orders: Backlinks::new(),
+    };
+    let mut alice = new_customer("alice");
+    let mut bob = new_customer("bob");
+    customer_box.put_many(vec![&mut alice, &mut bob])?;
+
+    let new_order = |status: &str, customer_id| Order {
+        id: 0,
+        status: status.to_string(),
+        customer: ToOne::new(customer_id),
+    };
+    let mut open_alice = new_order("open", alice.id);
+    let mut shipped_alice = new_order("shipped", alice.id);
+    let mut shipped_bob = new_order("shipped", bob.id);
+    order_box.put_many(vec![&mut open_alice, &mut shipped_alice, &mut shipped_bob])?;
+
+    let order_factory = new_order_condition_factory();
+    let customer_factory = new_customer_condition_factory();
+
+    // to-one link, from the orders to their customer
+    let query = order_box.query_link(
+        &order_factory.customer_link,
+        &mut customer_factory.name.eq("alice".to_string()),
+    )?;
+    let mut ids = query.find_ids()?;
+    ids.sort();
+    assert_eq!(vec![open_alice.id, shipped_alice.id], ids);
+
+    // combined with conditions on the orders themselves
+    let query = order_box
+        .query_builder(&mut order_factory.status.eq("shipped".to_string()))?
+        .link(
+            &order_factory.customer_link,
+            &mut customer_factory.name.eq("alice".to_string()),
+        )?
+        .build()?;
+    assert_eq!(vec![shipped_alice.id], query.find_ids()?);
+
+    // the backlink, from the customers to their orders
+    let query = customer_box.query_link(
+        &order_factory.customer_link.backlink(),
+        &mut order_factory.status.eq("open".to_string()),
+    )?;
+    assert_eq!(vec![alice.id], query.find_ids()?);
+
+    // the generated backlink of the entity is the same link
+    let query = customer_box.query_link(
+        &customer_factory.orders_link,
+        &mut order_factory.status.eq("shipped".to_string()),
+    )?;
+    assert_eq!(2, query.count()?);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn standalone_link_query_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut teacher_box = store.get_box::<Teacher>()?;
+    teacher_box.remove_all()?;
+    let mut student_box = store.get_box::<Student>()?;
+    student_box.remove_all()?;
+
+    let new_teacher = |name: &str| Teacher {
+        id: 0,
+        name: name.to_string(),
+        students: Backlinks::new(),
+    };
+    let mut math = new_teacher("math");
+    let mut art = new_teacher("art");
+    teacher_box.put_many(vec![&mut math, &mut art])?;
+
+    let mut ann = Student {
+        id: 0,
+        name: "ann".to_string(),
+        teachers: ToMany::new(),
+    };
+    ann.teachers.add(&math);
+    ann.teachers.add(&art);
+    let mut ben = Student {
+        id: 0,
+        name: "ben".to_string(),
+        teachers: ToMany::new(),
+    };
+    ben.teachers.add(&art);
+    student_box.put_many(vec![&mut ann, &mut ben])?;
+
+    let student_factory = new_student_condition_factory();
+    let teacher_factory = new_teacher_condition_factory();
+
+    // to-many link, from the students to their teachers
+    let query = student_box.query_link(
+        &student_factory.teachers_link,
+        &mut teacher_factory.name.eq("math".to_string()),
+    )?;
+    assert_eq!(vec![ann.id], query.find_ids()?);
+
+    // the backlink, from the teachers to their students
+    let query = teacher_box.query_link(
+        &student_factory.teachers_link.backlink(),
+        &mut student_factory.name.eq("ben".to_string()),
+    )?;
+    assert_eq!(vec![art.id], query.find_ids()?);
+
+    let query = teacher_box.query_link(
+        &teacher_factory.students_link,
+        &mut student_factory.name.eq("ann".to_string()),
+    )?;
+    assert_eq!(2, query.count()?);
+
+    Ok(())
+}
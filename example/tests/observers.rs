@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use example::{
+    make_factory_map, make_model, new_entity3_condition_factory, Entity2, Entity3,
+    Entity3ConditionFactory,
+};
+use objectbox::{error, opt::Opt, store::Store};
+
+use serial_test::serial;
+
+mod common;
+use common::new_entity3;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+#[serial]
+fn observer_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+    let mut box2 = store.get_box::<Entity2>()?;
+    box2.remove_all()?;
+
+    // channel, one event per committed write of the type
+    {
+        let (observer, receiver) = store.observe_channel::<Entity3>()?;
+        box3.put(&mut new_entity3("a"))?;
+        let entity_id = receiver.recv_timeout(TIMEOUT).expect("an event");
+
+        // a write to a different type is not delivered
+        box2.put(&mut Entity2 {
+            id: 0,
+            index_u64: 1,
+        })?;
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+
+        // one transaction, one event
+        store.write_tx(|tx| {
+            let mut b3 = tx.get_box::<Entity3>()?;
+            b3.put(&mut new_entity3("b"))?;
+            b3.put(&mut new_entity3("c"))?;
+            Ok(())
+        })?;
+        assert_eq!(entity_id, receiver.recv_timeout(TIMEOUT).expect("an event"));
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+
+        // dropping the observer disconnects the receiver
+        drop(observer);
+        box3.put(&mut new_entity3("d"))?;
+        assert_eq!(
+            Err(mpsc::RecvTimeoutError::Disconnected),
+            receiver.recv_timeout(TIMEOUT)
+        );
+    }
+
+    // closure
+    {
+        let count = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::channel();
+        let observer = {
+            let count = count.clone();
+            store.observe::<Entity3>(move || {
+                count.fetch_add(1, Ordering::SeqCst);
+                let _ = sender.send(());
+            })?
+        };
+        box3.remove_all()?;
+        receiver.recv_timeout(TIMEOUT).expect("a call");
+        assert_eq!(1, count.load(Ordering::SeqCst));
+        drop(observer);
+    }
+
+    // all types
+    {
+        let (sender, receiver) = mpsc::channel();
+        let _observer = store.observe_all(move |ids| {
+            let _ = sender.send(ids.to_vec());
+        })?;
+        box2.put(&mut Entity2 {
+            id: 0,
+            index_u64: 2,
+        })?;
+        let ids = receiver.recv_timeout(TIMEOUT).expect("an event");
+        assert_eq!(1, ids.len());
+    }
+
+    // a panic in the callback doesn't unwind into the store, later writes still call back
+    {
+        let (sender, receiver) = mpsc::channel();
+        let _observer = store.observe::<Entity3>(move || {
+            let _ = sender.send(());
+            panic!("observer callback");
+        })?;
+        box3.put(&mut new_entity3("e"))?;
+        receiver.recv_timeout(TIMEOUT).expect("a call");
+        box3.put(&mut new_entity3("f"))?;
+        receiver.recv_timeout(TIMEOUT).expect("a call");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn query_subscription_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+    let mut box2 = store.get_box::<Entity2>()?;
+    box2.remove_all()?;
+
+    let Entity3ConditionFactory { hello, .. } = new_entity3_condition_factory();
+    let query = box3.query(&mut hello.contains("world"))?;
+
+    let names = |objects: Vec<Entity3>| -> Vec<String> {
+        let mut names: Vec<String> = objects.into_iter().map(|e| e.hello).collect();
+        names.sort();
+        names
+    };
+
+    // the current result right away, then after each write
+    {
+        let (sender, receiver) = mpsc::channel();
+        let subscription = query.subscribe(move |objects| {
+            let _ = sender.send(objects);
+        })?;
+        assert!(receiver.recv_timeout(TIMEOUT).expect("a result").is_empty());
+
+        box3.put(&mut new_entity3("hello world"))?;
+        assert_eq!(
+            vec!["hello world"],
+            names(receiver.recv_timeout(TIMEOUT).expect("a result"))
+        );
+
+        // re-run, even if the write doesn't match
+        box3.put(&mut new_entity3("hello"))?;
+        assert_eq!(
+            vec!["hello world"],
+            names(receiver.recv_timeout(TIMEOUT).expect("a result"))
+        );
+        drop(subscription);
+    }
+
+    // unchanged results are skipped
+    {
+        let (sender, receiver) = mpsc::channel();
+        let _subscription = query.subscribe_distinct(move |objects| {
+            let _ = sender.send(objects);
+        })?;
+        assert_eq!(
+            vec!["hello world"],
+            names(receiver.recv_timeout(TIMEOUT).expect("a result"))
+        );
+
+        box3.put(&mut new_entity3("bye"))?;
+        box2.put(&mut Entity2 {
+            id: 0,
+            index_u64: 1,
+        })?;
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+
+        box3.put(&mut new_entity3("brave new world"))?;
+        assert_eq!(
+            vec!["brave new world", "hello world"],
+            names(receiver.recv_timeout(TIMEOUT).expect("a result"))
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use example::{make_factory_map, make_model, new_nullable_condition_factory, Nullable};
+use objectbox::{error, opt::Opt, store::Store};
+
+fn new_nulls() -> Nullable {
+    Nullable {
+        id: 0,
+        o_bool: None,
+        o_u8: None,
+        o_i16: None,
+        o_u32: None,
+        o_i64: None,
+        o_f32: None,
+        o_f64: None,
+        o_char: None,
+        o_string: None,
+        o_vec_bytes: None,
+        o_vec_string: None,
+    }
+}
+
+// zeroes and empty values are not null
+fn new_zeroes() -> Nullable {
+    Nullable {
+        id: 0,
+        o_bool: Some(false),
+        o_u8: Some(0),
+        o_i16: Some(0),
+        o_u32: Some(0),
+        o_i64: Some(0),
+        o_f32: Some(0.0),
+        o_f64: Some(0.0),
+        o_char: Some('\0'),
+        o_string: Some(String::new()),
+        o_vec_bytes: Some(Vec::new()),
+        o_vec_string: Some(Vec::new()),
+    }
+}
+
+#[test]
+fn optional_fields_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Nullable>()?;
+
+    let mut nulls = new_nulls();
+    let mut zeroes = new_zeroes();
+    let mut values = Nullable {
+        id: 0,
+        o_bool: Some(true),
+        o_u8: Some(250),
+        o_i16: Some(-2),
+        o_u32: Some(3),
+        o_i64: Some(-4),
+        o_f32: Some(5.5),
+        o_f64: Some(6.5),
+        o_char: Some('c'),
+        o_string: Some("hello".to_string()),
+        o_vec_bytes: Some(vec![1, 2]),
+        o_vec_string: Some(vec!["a".to_string()]),
+    };
+    box1.put_many(vec![&mut nulls, &mut zeroes, &mut values])?;
+
+    // read back as they were put
+    for o in [&nulls, &zeroes, &values] {
+        assert_eq!(Some(o), box1.get(o.id)?.as_ref());
+    }
+
+    // absent slots are null
+    let cf = new_nullable_condition_factory();
+    for mut condition in [
+        cf.o_bool.is_null(),
+        cf.o_u8.is_null(),
+        cf.o_i64.is_null(),
+        cf.o_f64.is_null(),
+        cf.o_string.is_null(),
+        cf.o_vec_bytes.is_null(),
+    ] {
+        let ids = box1.query(&mut condition)?.find_ids()?;
+        assert_eq!(vec![nulls.id], ids);
+    }
+    assert_eq!(2, box1.query(&mut cf.o_string.is_not_null())?.count()?);
+    assert_eq!(2, box1.query(&mut cf.o_i16.is_not_null())?.count()?);
+
+    // the views are nullable too
+    store.read_tx(|tx| {
+        let null_view = box1.get_ref(tx, nulls.id)?.expect("a view");
+        assert_eq!(None, null_view.o_string());
+        assert_eq!(None, null_view.o_u32());
+        let zero_view = box1.get_ref(tx, zeroes.id)?.expect("a view");
+        assert_eq!(Some(""), zero_view.o_string());
+        assert_eq!(Some(0), zero_view.o_u32());
+        Ok(())
+    })?;
+
+    Ok(())
+}
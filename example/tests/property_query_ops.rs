@@ -0,0 +1,117 @@
+use example::{
+    make_factory_map, make_model, new_entity3_condition_factory, new_entity_condition_factory,
+    Entity, Entity3, EntityConditionFactory,
+};
+use objectbox::{error, opt::Opt, store::Store};
+
+use serial_test::serial;
+
+mod common;
+use common::new_entity;
+
+#[test]
+#[serial]
+fn property_query_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box1 = store.get_box::<Entity>()?;
+    box1.remove_all()?;
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+
+    let EntityConditionFactory {
+        t_i64, t_f64, t_i8, ..
+    } = new_entity_condition_factory();
+
+    box1.put_many(vec![
+        &mut new_entity(1, 1.5, -2),
+        &mut new_entity(2, 2.5, -2),
+        &mut new_entity(6, 5.0, 3),
+    ])?;
+
+    let query = box1.query(&mut t_i64.gt(0))?;
+
+    // integers
+    {
+        let prop = query.property(&t_i64)?;
+        assert_eq!(3, prop.count()?);
+        assert_eq!(Some(1), prop.min()?);
+        assert_eq!(Some(6), prop.max()?);
+        assert_eq!(9, prop.sum()?);
+        assert_eq!(Some(3.0), prop.avg()?);
+        assert_eq!(Some(3), prop.avg_int()?);
+    }
+
+    // floating point
+    {
+        let prop = query.property(&t_f64)?;
+        assert_eq!(Some(1.5), prop.min()?);
+        assert_eq!(Some(5.0), prop.max()?);
+        assert_eq!(9.0, prop.sum()?);
+    }
+
+    // distinct
+    {
+        let prop = query.property(&t_i8)?;
+        assert_eq!(3, prop.count()?);
+        assert_eq!(2, prop.distinct(true)?.count()?);
+        assert_eq!(-1, prop.distinct(false)?.sum()?);
+    }
+
+    // values
+    {
+        let mut values = query.property(&t_i64)?.find_values()?;
+        values.sort();
+        assert_eq!(vec![1, 2, 6], values);
+
+        let mut values = query.property(&t_f64)?.find_values_or(0.0)?;
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(vec![1.5, 2.5, 5.0], values);
+
+        let prop = query.property(&t_i8)?;
+        assert_eq!(3, prop.find_values()?.len());
+        let mut values = prop.distinct(true)?.find_values()?;
+        values.sort();
+        assert_eq!(vec![-2, 3], values);
+    }
+
+    // no matches
+    {
+        let empty = box1.query(&mut t_i64.gt(100))?;
+        let prop = empty.property(&t_i64)?;
+        assert_eq!(0, prop.count()?);
+        assert_eq!(None, prop.min()?);
+        assert_eq!(0, prop.sum()?);
+    }
+
+    // strings
+    {
+        let hello = new_entity3_condition_factory().hello;
+        box3.put_many(vec![
+            &mut Entity3 {
+                id: 0,
+                hello: "a".to_string(),
+            },
+            &mut Entity3 {
+                id: 0,
+                hello: "A".to_string(),
+            },
+        ])?;
+        let query3 = box3.query(&mut hello.starts_with(""))?;
+        let prop = query3.property(&hello)?;
+        assert_eq!(2, prop.distinct_case(true, true)?.count()?);
+        assert_eq!(1, prop.distinct_case(true, false)?.count()?);
+
+        let mut values = prop.distinct(false)?.find_values()?;
+        values.sort();
+        assert_eq!(vec!["A".to_string(), "a".to_string()], values);
+    }
+
+    box1.remove_all()?;
+    box3.remove_all()?;
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use example::{make_factory_map, make_model, Entity3, Student, Teacher};
+use objectbox::c::{OBXPutMode_INSERT, OBXPutMode_PUT, OBXPutMode_UPDATE};
+use objectbox::relations::{Backlinks, ToMany};
+use objectbox::{error, opt::Opt, store::Store};
+
+mod common;
+use common::new_entity3;
+
+#[test]
+fn insert_update_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+
+    // insert assigns an id to new objects
+    let mut a = new_entity3("a");
+    let id = box3.insert(&mut a)?;
+    assert_eq!(id, a.id);
+
+    // but fails for an existing id
+    let err = box3.insert(&mut a).unwrap_err();
+    assert_eq!(error::ErrorKind::IdAlreadyExists, err.kind());
+
+    // update only changes existing objects
+    a.hello = "b".to_string();
+    assert_eq!(id, box3.update(&mut a)?);
+    assert_eq!("b", box3.get(id)?.expect("object").hello);
+
+    box3.remove_with_id(id)?;
+    let err = box3.update(&mut a).unwrap_err();
+    assert_eq!(error::ErrorKind::IdNotFound, err.kind());
+    assert!(box3.update(&mut new_entity3("new")).is_err());
+    assert_eq!(0, box3.count()?);
+
+    // the explicit modes
+    let mut c = new_entity3("c");
+    let id = box3.put_with_mode(&mut c, OBXPutMode_PUT)?;
+    assert_eq!(id, box3.put_with_mode(&mut c, OBXPutMode_UPDATE)?);
+    assert!(box3.put_with_mode(&mut c, OBXPutMode_INSERT).is_err());
+    assert_eq!(id, c.id);
+
+    let mut d = new_entity3("d");
+    assert!(box3.put_with_mode(&mut d, OBXPutMode_UPDATE).is_err());
+    assert_eq!(0, d.id);
+
+    Ok(())
+}
+
+#[test]
+fn put_many_with_mode_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+
+    let mut existing = new_entity3("existing");
+    let existing_id = box3.put(&mut existing)?;
+
+    // one bad id fails the whole batch
+    {
+        let mut fresh = new_entity3("fresh");
+        let result =
+            box3.put_many_with_mode(vec![&mut fresh, &mut existing], OBXPutMode_INSERT, true);
+        assert_eq!(
+            error::ErrorKind::IdAlreadyExists,
+            result.unwrap_err().kind()
+        );
+        assert_eq!(1, box3.count()?);
+        // the rollback also resets the id of the object put before
+        assert_eq!(0, fresh.id);
+    }
+
+    // or only the bad ids are skipped
+    {
+        let mut fresh = new_entity3("fresh");
+        let ids =
+            box3.put_many_with_mode(vec![&mut fresh, &mut existing], OBXPutMode_INSERT, false)?;
+        assert_eq!(vec![fresh.id, 0], ids);
+        assert_eq!(2, box3.count()?);
+    }
+
+    // updates skip new and removed objects
+    {
+        let mut removed = new_entity3("removed");
+        box3.put(&mut removed)?;
+        box3.remove_with_id(removed.id)?;
+
+        let mut new = new_entity3("new");
+        existing.hello = "updated".to_string();
+        let ids = box3.put_many_with_mode(
+            vec![&mut new, &mut existing, &mut removed],
+            OBXPutMode_UPDATE,
+            false,
+        )?;
+        assert_eq!(vec![0, existing_id, 0], ids);
+        assert_eq!(0, new.id);
+        assert_eq!("updated", box3.get(existing_id)?.expect("object").hello);
+        assert_eq!(2, box3.count()?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn update_relations_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut ada = Teacher {
+        id: 0,
+        name: "ada".to_string(),
+        students: Backlinks::new(),
+    };
+    store.get_box::<Teacher>()?.put(&mut ada)?;
+
+    let mut student_box = store.get_box::<Student>()?;
+    let mut student = Student {
+        id: 0,
+        name: "grace".to_string(),
+        teachers: ToMany::new(),
+    };
+    student_box.insert(&mut student)?;
+
+    // pending relation changes are applied with the update, like with put
+    student.teachers.add(&ada);
+    student_box.update(&mut student)?;
+    assert!(!student.teachers.has_pending_changes());
+    assert_eq!(
+        vec![ada.id],
+        student_box
+            .get(student.id)?
+            .expect("object")
+            .teachers
+            .target_ids(&store)?
+    );
+
+    Ok(())
+}
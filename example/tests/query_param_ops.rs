@@ -0,0 +1,89 @@
+use example::{
+    make_factory_map, make_model, new_entity3_condition_factory, new_entity_condition_factory,
+    Entity, Entity3, EntityConditionFactory,
+};
+use objectbox::{error, opt::Opt, store::Store};
+
+use serial_test::serial;
+
+mod common;
+use common::new_entity;
+
+#[test]
+#[serial]
+fn query_param_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box1 = store.get_box::<Entity>()?;
+    box1.remove_all()?;
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+
+    let EntityConditionFactory { t_i64, t_f64, .. } = new_entity_condition_factory();
+
+    box1.put_many(vec![
+        &mut new_entity(1, 1.5, 3),
+        &mut new_entity(2, 2.5, 3),
+        &mut new_entity(6, 5.0, 3),
+    ])?;
+
+    // params, typed by the condition factory field
+    {
+        let query = box1.query(&mut t_i64.gt(0))?;
+        assert_eq!(3, query.count()?);
+        assert_eq!(1, query.set_param(&t_i64, 2i64)?.count()?);
+        assert_eq!(0, query.set_param(&t_i64, 6i64)?.count()?);
+
+        let query = box1.query(&mut t_f64.between(0.0, 2.0))?;
+        assert_eq!(1, query.count()?);
+        assert_eq!(3, query.set_param(&t_f64, (0.0, 6.0))?.count()?);
+
+        let query = box1.query(&mut t_i64.member_of(vec![1]))?;
+        assert_eq!(1, query.count()?);
+        assert_eq!(2, query.set_param(&t_i64, vec![1i64, 6])?.count()?);
+    }
+
+    // aliases, two conditions on the same property
+    {
+        let mut condition = t_i64.ge(0).alias("min") & t_i64.le(10).alias("max");
+        let query = box1.query(&mut condition)?;
+        assert_eq!(3, query.count()?);
+        query.set_alias("min", 2i64)?.set_alias("max", 5i64)?;
+        assert_eq!(1, query.count()?);
+        assert!(query.set_alias("unknown", 1i64).is_err());
+    }
+
+    // an alias that isn't a valid C string fails the query
+    assert!(box1.query(&mut t_i64.ge(0).alias("nul\0")).is_err());
+
+    // strings
+    {
+        let hello = new_entity3_condition_factory().hello;
+        box3.put_many(vec![
+            &mut Entity3 {
+                id: 0,
+                hello: "a".to_string(),
+            },
+            &mut Entity3 {
+                id: 0,
+                hello: "b".to_string(),
+            },
+        ])?;
+
+        let query = box3.query(&mut hello.eq("a".to_string()))?;
+        assert_eq!("a", query.find()?[0].hello);
+        assert_eq!("b", query.set_param(&hello, "b")?.find()?[0].hello);
+
+        let query = box3.query(&mut hello.starts_with("x").alias("prefix"))?;
+        assert_eq!(0, query.count()?);
+        assert_eq!(1, query.set_alias("prefix", "a")?.count()?);
+    }
+
+    box1.remove_all()?;
+    box3.remove_all()?;
+
+    Ok(())
+}
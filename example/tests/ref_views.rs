@@ -0,0 +1,129 @@
+use example::{
+    make_factory_map, make_model, new_entity3_condition_factory, Entity, Entity3,
+    Entity3ConditionFactory, Order,
+};
+use objectbox::relations::ToOne;
+use objectbox::{error, opt::Opt, store::Store};
+
+#[test]
+fn get_ref_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Entity>()?;
+    let mut order_box = store.get_box::<Order>()?;
+
+    let mut entity = Entity {
+        id: 0,
+        index_u32: 1,
+        t_bool: true,
+        t_u8: 2,
+        t_i8: -3,
+        t_i16: -4,
+        t_u16: 5,
+        unique_i32: 6,
+        t_i32: -7,
+        t_u32: 8,
+        t_u64: 9,
+        t_i64: -10,
+        t_f32: 11.5,
+        t_f64: 12.5,
+        t_string: "13".to_string(),
+        t_char: 'x',
+        t_vec_string: vec!["a".to_string(), "b".to_string()],
+        t_vec_bytes: vec![1, 2, 3],
+    };
+    let id = box1.put(&mut entity)?;
+
+    let mut order = Order {
+        id: 0,
+        status: "open".to_string(),
+        customer: ToOne::new(42),
+    };
+    let order_id = order_box.put(&mut order)?;
+
+    store.read_tx(|tx| {
+        let view = box1.get_ref(tx, id)?.expect("a view");
+        assert_eq!(id, view.id());
+        assert!(view.t_bool());
+        assert_eq!(2, view.t_u8());
+        assert_eq!(-3, view.t_i8());
+        assert_eq!(-4, view.t_i16());
+        assert_eq!(5, view.t_u16());
+        assert_eq!(-7, view.t_i32());
+        assert_eq!(8, view.t_u32());
+        assert_eq!(9, view.t_u64());
+        assert_eq!(-10, view.t_i64());
+        assert_eq!(11.5, view.t_f32());
+        assert_eq!(12.5, view.t_f64());
+        assert_eq!("13", view.t_string());
+        assert_eq!('x', view.t_char());
+        let strings: Vec<&str> = view.t_vec_string().iter().collect();
+        assert_eq!(vec!["a", "b"], strings);
+        assert_eq!(&[1, 2, 3], view.t_vec_bytes());
+
+        let order_view = order_box.get_ref(tx, order_id)?.expect("a view");
+        assert_eq!("open", order_view.status());
+        assert_eq!(42, order_view.customer_id());
+
+        assert!(box1.get_ref(tx, id + 1000)?.is_none());
+        Ok(())
+    })?;
+
+    // the data of a write transaction may move
+    let result = store.write_tx(|tx| box1.get_ref(tx, id).map(|v| v.is_some()));
+    assert!(result.is_err());
+
+    // also to a read nested in a write, it runs over the same transaction
+    let result = store.write_tx(|tx| {
+        tx.read_tx(|read| {
+            assert!(read.is_nested_in_write());
+            box1.get_ref(read, id).map(|v| v.is_some())
+        })
+    });
+    assert!(result.is_err());
+    store.read_tx(|tx| {
+        tx.read_tx(|read| {
+            assert!(!read.is_nested_in_write());
+            assert!(box1.get_ref(read, id)?.is_some());
+            Ok(())
+        })
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn for_each_ref_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box3 = store.get_box::<Entity3>()?;
+
+    for hello in ["hello a", "hello b", "bye", "hello c"] {
+        box3.put(&mut Entity3 {
+            id: 0,
+            hello: hello.to_string(),
+        })?;
+    }
+
+    let Entity3ConditionFactory { hello, .. } = new_entity3_condition_factory();
+    let query = box3.query(&mut hello.starts_with("hello"))?;
+
+    let mut total_len = 0;
+    query.for_each_ref(|view| {
+        total_len += view.hello().len();
+        true
+    })?;
+    assert_eq!(21, total_len);
+
+    // stops when the closure returns false
+    let mut visited = 0;
+    query.for_each_ref(|_| {
+        visited += 1;
+        visited < 2
+    })?;
+    assert_eq!(2, visited);
+
+    Ok(())
+}
@@ -0,0 +1,353 @@
+use example::{
+    make_factory_map, make_model, new_customer_condition_factory, new_order_condition_factory,
+    new_student_condition_factory, new_teacher_condition_factory, Customer, Order, Student,
+    Teacher,
+};
+use objectbox::{
+    error,
+    opt::Opt,
+    relations::{Backlinks, ToMany, ToOne},
+    store::Store,
+};
+
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn to_one_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut customer_box = store.get_box::<Customer>()?;
+    customer_box.remove_all()?;
+    let mut order_box = store.get_box::<Order>()?;
+    order_box.remove_all()?;
+
+    let mut alice = Customer {
+        id: 0,
+        name: "alice".to_string(),
+        orders: Backlinks::new(),
+    };
+    let mut bob = Customer {
+        id: 0,
+        name: "bob".to_string(),
+        orders: Backlinks::new(),
+    };
+    customer_box.put_many(vec![&mut alice, &mut bob])?;
+
+    let mut order1 = Order {
+        id: 0,
+        status: "open".to_string(),
+        customer: ToOne::default(),
+    };
+    order1.customer.set_target(Some(&alice));
+    let mut order2 = Order {
+        id: 0,
+        status: "shipped".to_string(),
+        customer: ToOne::new(bob.id),
+    };
+    let mut order3 = Order {
+        id: 0,
+        status: "open".to_string(),
+        customer: ToOne::default(),
+    };
+    order_box.put_many(vec![&mut order1, &mut order2, &mut order3])?;
+
+    // the target id is stored, the target is read lazily
+    {
+        let read1 = order_box.get(order1.id)?.unwrap();
+        assert_eq!(alice.id, read1.customer.target_id());
+        assert_eq!("alice", read1.customer.target(&store)?.unwrap().name);
+
+        let read3 = order_box.get(order3.id)?.unwrap();
+        assert_eq!(0, read3.customer.target_id());
+        assert!(read3.customer.target(&store)?.is_none());
+    }
+
+    // retarget, and clear
+    {
+        let mut read1 = order_box.get(order1.id)?.unwrap();
+        read1.customer.set_target(Some(&bob));
+        order_box.put(&mut read1)?;
+        let read1 = order_box.get(order1.id)?.unwrap();
+        assert_eq!("bob", read1.customer.target(&store)?.unwrap().name);
+
+        let mut read2 = order_box.get(order2.id)?.unwrap();
+        read2.customer.set_target(None);
+        order_box.put(&mut read2)?;
+        assert_eq!(0, order_box.get(order2.id)?.unwrap().customer.target_id());
+    }
+
+    // a removed target is resolved as None
+    {
+        let mut carol = Customer {
+            id: 0,
+            name: "carol".to_string(),
+            orders: Backlinks::new(),
+        };
+        customer_box.put(&mut carol)?;
+        let mut order4 = Order {
+            id: 0,
+            status: "open".to_string(),
+            customer: ToOne::new(carol.id),
+        };
+        order_box.put(&mut order4)?;
+        customer_box.remove_with_id(carol.id)?;
+        let read4 = order_box.get(order4.id)?.unwrap();
+        assert!(read4.customer.target(&store)?.is_none());
+    }
+
+    // conditions on the target id, and on the target through links
+    {
+        let order_factory = new_order_condition_factory();
+        let customer_factory = new_customer_condition_factory();
+
+        let query = order_box.query(&mut order_factory.customer.eq(bob.id as i64))?;
+        assert_eq!(vec![order1.id], query.find_ids()?);
+
+        let query = order_box.query_link(
+            &order_factory.customer_link,
+            &mut customer_factory.name.contains("bob"),
+        )?;
+        assert_eq!(vec![order1.id], query.find_ids()?);
+
+        let query = customer_box.query_link(
+            &order_factory.customer_link.backlink(),
+            &mut order_factory.status.contains("open"),
+        )?;
+        assert_eq!(vec![bob.id], query.find_ids()?);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn to_many_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut teacher_box = store.get_box::<Teacher>()?;
+    teacher_box.remove_all()?;
+    let mut student_box = store.get_box::<Student>()?;
+    student_box.remove_all()?;
+
+    let mut ada = Teacher {
+        id: 0,
+        name: "ada".to_string(),
+        students: Backlinks::new(),
+    };
+    let mut alan = Teacher {
+        id: 0,
+        name: "alan".to_string(),
+        students: Backlinks::new(),
+    };
+    teacher_box.put_many(vec![&mut ada, &mut alan])?;
+
+    let mut student = Student {
+        id: 0,
+        name: "grace".to_string(),
+        teachers: ToMany::new(),
+    };
+    student.teachers.add(&ada);
+    student.teachers.add(&alan);
+    assert!(student.teachers.has_pending_changes());
+    // pending changes are visible before the put
+    assert_eq!(vec![ada.id, alan.id], student.teachers.target_ids(&store)?);
+
+    // the relation is put with the student
+    student_box.put(&mut student)?;
+    assert!(!student.teachers.has_pending_changes());
+    assert_eq!(
+        vec![ada.id, alan.id],
+        student_box
+            .get(student.id)?
+            .unwrap()
+            .teachers
+            .target_ids(&store)?
+    );
+
+    // read through the stored source object
+    {
+        let read = student_box.get(student.id)?.unwrap();
+        let mut names: Vec<String> = read
+            .teachers
+            .targets(&store)?
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        names.sort();
+        assert_eq!(vec!["ada", "alan"], names);
+    }
+
+    // remove, only applied on put
+    {
+        let mut read = student_box.get(student.id)?.unwrap();
+        read.teachers.remove(&ada);
+        assert_eq!(vec![alan.id], read.teachers.target_ids(&store)?);
+        let stored = student_box.get(student.id)?.unwrap();
+        assert_eq!(2, stored.teachers.target_ids(&store)?.len());
+
+        student_box.put(&mut read)?;
+        let read = student_box.get(student.id)?.unwrap();
+        assert_eq!(vec![alan.id], read.teachers.target_ids(&store)?);
+    }
+
+    // adds and removes are rolled back with the transaction of the put
+    {
+        let mut read = student_box.get(student.id)?.unwrap();
+        read.teachers.add(&ada);
+        let result: error::Result<()> = store.write_tx(|tx| {
+            tx.get_box::<Student>()?.put(&mut read)?;
+            error::Error::new_local("rollback").as_result()
+        });
+        assert!(result.is_err());
+        let stored = student_box.get(student.id)?.unwrap();
+        assert_eq!(vec![alan.id], stored.teachers.target_ids(&store)?);
+
+        // the changes are still pending, and land with the next put
+        assert!(read.teachers.has_pending_changes());
+        student_box.put(&mut read)?;
+        assert!(!read.teachers.has_pending_changes());
+        let mut ids = student_box
+            .get(student.id)?
+            .unwrap()
+            .teachers
+            .target_ids(&store)?;
+        ids.sort();
+        assert_eq!(vec![ada.id, alan.id], ids);
+    }
+
+    // conditions on the targets through links
+    {
+        let student_factory = new_student_condition_factory();
+        let teacher_factory = new_teacher_condition_factory();
+
+        let query = student_box.query_link(
+            &student_factory.teachers_link,
+            &mut teacher_factory.name.contains("alan"),
+        )?;
+        assert_eq!(vec![student.id], query.find_ids()?);
+
+        let query = teacher_box.query_link(
+            &student_factory.teachers_link.backlink(),
+            &mut student_factory.name.contains("grace"),
+        )?;
+        assert_eq!(vec![alan.id], query.find_ids()?);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn backlinks_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut customer_box = store.get_box::<Customer>()?;
+    customer_box.remove_all()?;
+    let mut order_box = store.get_box::<Order>()?;
+    order_box.remove_all()?;
+    let mut teacher_box = store.get_box::<Teacher>()?;
+    teacher_box.remove_all()?;
+    let mut student_box = store.get_box::<Student>()?;
+    student_box.remove_all()?;
+
+    // through a ToOne
+    {
+        let mut alice = Customer {
+            id: 0,
+            name: "alice".to_string(),
+            orders: Backlinks::new(),
+        };
+        // not put yet
+        assert!(alice.orders.source_ids(&store)?.is_empty());
+        customer_box.put(&mut alice)?;
+        assert!(alice.orders.sources(&store)?.is_empty());
+
+        let mut order1 = Order {
+            id: 0,
+            status: "open".to_string(),
+            customer: ToOne::new(alice.id),
+        };
+        let mut order2 = Order {
+            id: 0,
+            status: "shipped".to_string(),
+            customer: ToOne::new(alice.id),
+        };
+        order_box.put_many(vec![&mut order1, &mut order2])?;
+
+        // resolved on access, for the put and the read object
+        assert_eq!(vec![order1.id, order2.id], alice.orders.source_ids(&store)?);
+        let read = customer_box.get(alice.id)?.unwrap();
+        let statuses: Vec<String> = read
+            .orders
+            .sources(&store)?
+            .into_iter()
+            .map(|o| o.status)
+            .collect();
+        assert_eq!(vec!["open", "shipped"], statuses);
+
+        order_box.remove_with_id(order1.id)?;
+        assert_eq!(vec![order2.id], read.orders.source_ids(&store)?);
+    }
+
+    // through a standalone relation
+    {
+        let mut ada = Teacher {
+            id: 0,
+            name: "ada".to_string(),
+            students: Backlinks::new(),
+        };
+        teacher_box.put(&mut ada)?;
+
+        let mut grace = Student {
+            id: 0,
+            name: "grace".to_string(),
+            teachers: ToMany::new(),
+        };
+        let mut linus = Student {
+            id: 0,
+            name: "linus".to_string(),
+            teachers: ToMany::new(),
+        };
+        grace.teachers.add(&ada);
+        linus.teachers.add(&ada);
+        student_box.put_many(vec![&mut grace, &mut linus])?;
+
+        let read = teacher_box.get(ada.id)?.unwrap();
+        let mut names: Vec<String> = read
+            .students
+            .sources(&store)?
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        names.sort();
+        assert_eq!(vec!["grace", "linus"], names);
+
+        linus.teachers.remove(&ada);
+        student_box.put(&mut linus)?;
+        assert_eq!(vec![grace.id], read.students.source_ids(&store)?);
+    }
+
+    // conditions on the sources through links
+    {
+        let customer_factory = new_customer_condition_factory();
+        let order_factory = new_order_condition_factory();
+
+        let query = customer_box.query_link(
+            &customer_factory.orders_link,
+            &mut order_factory.status.contains("shipped"),
+        )?;
+        assert_eq!(1, query.count()?);
+    }
+
+    Ok(())
+}
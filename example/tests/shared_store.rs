@@ -0,0 +1,68 @@
+use std::thread;
+
+use example::{make_factory_map, make_model, Entity3};
+use objectbox::{
+    error,
+    opt::Opt,
+    store::{SharedStore, Store},
+};
+
+use serial_test::serial;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+#[serial]
+fn shared_store_tests() -> error::Result<()> {
+    assert_send_sync::<SharedStore>();
+
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = SharedStore::new(opt, trait_map)?;
+    store.get_box::<Entity3>()?.remove_all()?;
+
+    // each thread takes its own box
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let store = store.clone();
+            thread::spawn(move || -> error::Result<()> {
+                let mut box3 = store.get_box::<Entity3>()?;
+                for j in 0..10 {
+                    box3.put(&mut Entity3 {
+                        id: 0,
+                        hello: format!("{i}-{j}"),
+                    })?;
+                }
+                store.write_tx(|tx| {
+                    tx.get_box::<Entity3>()?.put(&mut Entity3 {
+                        id: 0,
+                        hello: format!("{i}-tx"),
+                    })
+                })?;
+                Ok(())
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("a finished thread")?;
+    }
+    assert_eq!(44, store.get_box::<Entity3>()?.count()?);
+
+    // the store stays open while a clone is alive
+    let clone = store.clone();
+    drop(store);
+    assert_eq!(44, clone.get_box::<Entity3>()?.count()?);
+    drop(clone);
+
+    // a plain store can be shared later
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let shared = Store::new(opt, make_factory_map())?.into_shared();
+    let count = thread::spawn(move || shared.get_box::<Entity3>()?.count())
+        .join()
+        .expect("a finished thread")?;
+    assert_eq!(44, count);
+
+    Ok(())
+}
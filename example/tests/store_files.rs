@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use example::{make_factory_map, make_model, Entity3};
+use objectbox::{error, opt::Opt, store::Store};
+
+mod common;
+use common::new_entity3;
+
+#[test]
+fn file_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary(opt, make_factory_map())?;
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.put_many(vec![&mut new_entity3("a"), &mut new_entity3("b")])?;
+    assert!(store.db_file_size()? > 0);
+
+    // unknown directory
+    let attached = Store::attach_id(store.id(), make_factory_map())?;
+    assert!(attached.db_file_size().is_err());
+    drop(attached);
+
+    // a snapshot, while the store stays open
+    let backup = std::env::temp_dir().join(format!("objectbox-backup-{}", std::process::id()));
+    store.backup_to(&backup)?;
+    box3.put(&mut new_entity3("c"))?;
+    assert_eq!(3, box3.count()?);
+    {
+        let opt = Opt::from_model(&mut make_model())?;
+        opt.directory(&backup)?;
+        let restored = Store::new(opt, make_factory_map())?;
+        assert_eq!(2, restored.get_box::<Entity3>()?.count()?);
+
+        // not over an open store
+        assert!(store.backup_to(&backup).is_err());
+        assert!(Store::remove_files(&backup).is_err());
+    }
+
+    Store::remove_files(&backup)?;
+    assert!(!backup.join("data.mdb").exists());
+    let _ = std::fs::remove_dir(&backup);
+    Ok(())
+}
+
+#[test]
+fn in_memory_file_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+    store.get_box::<Entity3>()?.put(&mut new_entity3("a"))?;
+    assert_eq!(0, store.db_file_size()?);
+    assert!(store
+        .backup_to(&PathBuf::from("objectbox-in-memory-backup"))
+        .is_err());
+    Ok(())
+}
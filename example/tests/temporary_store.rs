@@ -0,0 +1,63 @@
+use std::thread;
+
+use example::{make_factory_map, make_model, Entity3};
+use objectbox::{error, opt::Opt, store::Store};
+
+// no #[serial], and no remove_all, each test has its own store
+
+fn put_and_count(store: &Store, hello: &str) -> error::Result<u64> {
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.put(&mut Entity3 {
+        id: 0,
+        hello: hello.to_string(),
+    })?;
+    box3.count()
+}
+
+#[test]
+fn temporary_store_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary(opt, make_factory_map())?;
+    assert_eq!(1, put_and_count(&store, "a")?);
+
+    // isolated from other temporary stores, also on other threads
+    let other = thread::spawn(|| -> error::Result<u64> {
+        let opt = Opt::from_model(&mut make_model())?;
+        let store = Store::temporary(opt, make_factory_map())?;
+        put_and_count(&store, "b")?;
+        put_and_count(&store, "c")
+    });
+    assert_eq!(2, other.join().expect("a finished thread")?);
+    assert_eq!(2, put_and_count(&store, "d")?);
+    Ok(())
+}
+
+#[test]
+fn temporary_in_memory_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+    assert_eq!(1, put_and_count(&store, "a")?);
+
+    let opt = Opt::from_model(&mut make_model())?;
+    let other = Store::temporary_in_memory(opt, make_factory_map())?;
+    assert_eq!(1, put_and_count(&other, "b")?);
+    assert_ne!(store.id(), other.id());
+    Ok(())
+}
+
+#[test]
+fn in_memory_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    opt.in_memory("in_memory_tests")?;
+    assert_eq!("memory:in_memory_tests", opt.get_directory());
+    let store = Store::new(opt, make_factory_map())?;
+    assert_eq!(1, put_and_count(&store, "a")?);
+    drop(store);
+
+    // the data is gone with the last store of the name
+    let opt = Opt::from_model(&mut make_model())?;
+    opt.in_memory("in_memory_tests")?;
+    let store = Store::new(opt, make_factory_map())?;
+    assert_eq!(1, put_and_count(&store, "b")?);
+    Ok(())
+}
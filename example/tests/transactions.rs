@@ -0,0 +1,110 @@
+use example::{make_factory_map, make_model, new_entity3_condition_factory, Entity, Entity3};
+use objectbox::{error, opt::Opt, store::Store};
+
+use serial_test::serial;
+
+mod common;
+use common::new_entity3;
+
+#[test]
+#[serial]
+fn transaction_tests() -> error::Result<()> {
+    let mut model = make_model();
+    let opt = Opt::from_model(&mut model)?;
+    let trait_map = make_factory_map();
+    let store = Store::new(opt, trait_map)?;
+
+    let mut box1 = store.get_box::<Entity>()?;
+    box1.remove_all()?;
+    let mut box3 = store.get_box::<Entity3>()?;
+    box3.remove_all()?;
+
+    // commit on Ok, spanning two entity types
+    {
+        let id = store.write_tx(|tx| {
+            let mut b1 = tx.get_box::<Entity>()?;
+            let mut b3 = tx.get_box::<Entity3>()?;
+            b3.put_many(vec![&mut new_entity3("a"), &mut new_entity3("b")])?;
+            let mut e = Entity {
+                id: 0,
+                index_u32: 1,
+                t_bool: false,
+                t_u8: 2,
+                t_i8: 3,
+                t_i16: 4,
+                t_u16: 5,
+                unique_i32: 6,
+                t_i32: 7,
+                t_u32: 8,
+                t_u64: 9,
+                t_i64: 11,
+                t_f32: 12.0,
+                t_f64: 13.0,
+                t_string: "14".to_string(),
+                t_char: 'c',
+                t_vec_string: vec!["str1".to_string()],
+                t_vec_bytes: vec![0x9],
+            };
+            let id = b1.put(&mut e)?;
+
+            let (_, size_change) = tx.data_size()?;
+            assert!(size_change > 0);
+
+            // a nested read sees the uncommitted writes
+            let count = tx.read_tx(|rtx| rtx.get_box::<Entity3>()?.count())?;
+            assert_eq!(2, count);
+            Ok(id)
+        })?;
+
+        assert!(box1.get(id)?.is_some());
+        assert_eq!(2, box3.count()?);
+    }
+
+    // abort on Err
+    {
+        let result: error::Result<()> = store.write_tx(|tx| {
+            tx.get_box::<Entity3>()?.put(&mut new_entity3("c"))?;
+            error::Error::new_local("rollback").as_result()
+        });
+        assert!(result.is_err());
+        assert_eq!(2, box3.count()?);
+    }
+
+    // abort on panic
+    {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = store.write_tx(|tx| -> error::Result<()> {
+                tx.get_box::<Entity3>()?.put(&mut new_entity3("d"))?;
+                panic!("rollback");
+            });
+        }));
+        assert!(result.is_err());
+        assert_eq!(2, box3.count()?);
+    }
+
+    // read tx and queries inside a write tx
+    {
+        let all = store.read_tx(|tx| tx.get_box::<Entity3>()?.get_all())?;
+        assert_eq!(2, all.len());
+
+        let hello = new_entity3_condition_factory().hello;
+        let query = box3.query(&mut hello.contains("a"))?;
+        let removed = store.write_tx(|tx| query.remove_in_tx(tx))?;
+        assert_eq!(1, removed);
+        assert_eq!(1, box3.count()?);
+
+        // a query in a write tx sees the objects put in it
+        let query = box3.query(&mut hello.contains("b"))?;
+        let found = store.write_tx(|tx| {
+            tx.get_box::<Entity3>()?.put(&mut new_entity3("bb"))?;
+            query.find_in_tx(tx)
+        })?;
+        assert_eq!(2, found.len());
+        assert_eq!(2, box3.count()?);
+    }
+
+    box1.remove_all()?;
+    box3.remove_all()?;
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use example::{make_factory_map, make_model, Session};
+use objectbox::{error, opt::Opt, store::Store};
+
+#[test]
+fn transient_fields_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Session>()?;
+
+    let put_at = Instant::now();
+    let mut session = Session {
+        id: 0,
+        token: "t0k3n".to_string(),
+        cache: HashMap::from([("hits".to_string(), 3)]),
+        loaded_at: put_at,
+    };
+    let id = box1.put(&mut session)?;
+
+    // initialised on load, by Default::default() or the given fn
+    let read = box1.get(id)?.expect("an object");
+    assert_eq!("t0k3n", read.token);
+    assert!(read.cache.is_empty());
+    assert!(read.loaded_at >= put_at);
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use example::{make_factory_map, make_model, Entity};
+use example::{make_factory_map, make_model, Contact, Entity};
 use objectbox::{error, opt::Opt, store::Store};
 
 use serial_test::serial;
@@ -40,7 +40,59 @@ fn uniqueness_tests() -> error::Result<()> {
     // pretend this is a new object
     entity.id = 0;
 
-    assert!(box1.put(&mut entity).is_err());
+    let err = box1.put(&mut entity).unwrap_err();
+    assert_eq!(error::ErrorKind::UniqueViolated, err.kind());
+    assert!(err.kind().is_constraint());
+    assert_eq!(Some(10201), err.code());
+
+    Ok(())
+}
+
+fn new_contact(email: &str, handle: &str) -> Contact {
+    Contact {
+        id: 0,
+        email: email.to_string(),
+        handle: handle.to_string(),
+    }
+}
+
+#[test]
+fn on_conflict_tests() -> error::Result<()> {
+    let opt = Opt::from_model(&mut make_model())?;
+    let store = Store::temporary_in_memory(opt, make_factory_map())?;
+
+    let mut box1 = store.get_box::<Contact>()?;
+
+    // replace: the existing object with the same handle is removed
+    let first_id = box1.put(&mut new_contact("a@example.com", "a"))?;
+    let second_id = box1.put(&mut new_contact("b@example.com", "a"))?;
+    assert!(!box1.contains(first_id)?);
+    assert_eq!(1, box1.count()?);
+    assert_eq!("b@example.com", box1.get(second_id)?.expect("object").email);
+
+    // fail: the clashing property and object are reported
+    let mut clash = new_contact("b@example.com", "c");
+    let err = box1.put_checked(&mut clash).unwrap_err();
+    assert_eq!(error::ErrorKind::UniqueViolated, err.kind());
+    let violation = err.unique_violation().expect("a unique violation");
+    assert_eq!("email", violation.property);
+    assert_eq!(second_id, violation.existing_id);
+    assert_eq!(0, clash.id);
+
+    // upsert: update the existing object instead
+    clash.id = violation.existing_id;
+    assert_eq!(second_id, box1.put_checked(&mut clash)?);
+    assert_eq!("c", box1.get(second_id)?.expect("object").handle);
+    assert_eq!(1, box1.count()?);
+
+    // other errors are passed on, e.g. an id that was never assigned
+    let mut other = new_contact("d@example.com", "d");
+    other.id = u64::MAX / 2;
+    let err = box1.put_checked(&mut other).unwrap_err();
+    assert_ne!(error::ErrorKind::UniqueViolated, err.kind());
+    assert!(err.unique_violation().is_none());
+    assert_eq!(u64::MAX / 2, other.id);
+    assert_eq!(1, box1.count()?);
 
     Ok(())
 }
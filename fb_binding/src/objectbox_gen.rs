@@ -1,11 +1,10 @@
 use objectbox::c;
 use objectbox::flatbuffers;
-use objectbox::map;
 use objectbox::model;
 use objectbox::query::traits as qtraits;
 use objectbox::traits;
 use std::marker;
-use std::rc;
+use std::sync;
 impl traits::IdExt for crate::Entity {
     fn get_id(&self) -> c::obx_id {
         self.id
@@ -14,6 +13,7 @@ impl traits::IdExt for crate::Entity {
         self.id = id;
     }
 }
+impl traits::RelationExt for crate::Entity {}
 impl traits::FBOBBridge for crate::Entity {
     fn flatten(&self, builder: &mut flatbuffers::FlatBufferBuilder) {
         builder.reset();
@@ -189,12 +189,12 @@ pub fn make_model() -> model::Model {
         .last_property_id(15, 12538710810757874575)
         .last_entity_id(1, 6934213297317435850)
 }
-pub fn make_factory_map() -> map::AnyMap {
-    let mut map = map::AnyMap::new();
-    let f1 = rc::Rc::new(traits::Factory::<crate::Entity> {
+pub fn make_factory_map() -> traits::FactoryMap {
+    let mut map = traits::FactoryMap::new();
+    let f1 = sync::Arc::new(traits::Factory::<crate::Entity> {
         phantom_data: marker::PhantomData,
         schema_id: 1,
-    }) as rc::Rc<dyn traits::EntityFactoryExt<crate::Entity>>;
+    }) as sync::Arc<dyn traits::EntityFactoryExt<crate::Entity>>;
     map.insert(f1);
     map
 }
@@ -1,17 +1,18 @@
 use core::panic;
 use std::collections::HashSet;
-use std::fs;
 use std::path::PathBuf;
 
 use genco::fmt;
 use genco::prelude::*;
 
+use crate::model_json::converter_to_impl_blanket;
 use crate::model_json::prop_type_to_impl_blanket;
+use crate::model_json::time_value_conversion;
 use crate::model_json::ModelEntity;
 use crate::model_json::ModelInfo;
 use crate::model_json::ModelProperty;
 use crate::ob_consts;
-use crate::util::StringHelper;
+use crate::util::{write_if_changed, StringHelper};
 
 fn tokens_to_string(tokens: &Tokens<Rust>) -> Vec<u8> {
     let mut w = fmt::IoWriter::new(Vec::<u8>::new());
@@ -31,8 +32,11 @@ fn tokens_to_string(tokens: &Tokens<Rust>) -> Vec<u8> {
 trait CodeGenEntityExt {
     fn get_id_property(&self) -> Option<&ModelProperty>;
     fn generate_id_trait(&self) -> Tokens<Rust>;
+    fn generate_relation_trait(&self) -> Tokens<Rust>;
+    fn generate_unique_trait(&self) -> Tokens<Rust>;
     fn generate_fb_trait(&self) -> Tokens<Rust>;
     fn generate_ob_trait(&self) -> Tokens<Rust>;
+    fn generate_ref_view(&self) -> Tokens<Rust>;
     fn generate_query_trait_impls(&self) -> Tokens<Rust>;
 }
 
@@ -88,6 +92,17 @@ fn encode_flatten(
               builder.push_slot::<f64>($offset, self.$name, 0.0);
             }
         }
+        ob_consts::OBXPropertyType_Relation => {
+            quote! {
+              builder.push_slot::<u64>($offset, self.$name.target_id(), 0);
+            }
+        }
+        ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+            let to_i64 = time_value_conversion(field_type, true);
+            quote! {
+              builder.push_slot_always::<i64>($offset, $to_i64(&self.$name));
+            }
+        }
         _ => {
             let inferred_type_bits = match field_type {
                 ob_consts::OBXPropertyType_Byte => "8",
@@ -143,6 +158,123 @@ fn encode_to_fb_unnested(field_type: u32, offset: usize, name: &String) -> Token
     new_tokens
 }
 
+/// Option<T> fields, None leaves the slot absent, which reads as null
+fn encode_optional_to_fb_unnested(field_type: u32, offset: usize, name: &String) -> Tokens<Rust> {
+    let wip_offset = &rust::import("flatbuffers", "WIPOffset");
+
+    match field_type {
+        ob_consts::OBXPropertyType_StringVector => {
+            quote! {
+              let vec_$offset = self.$name.as_ref().map(|sv| {
+                let strs_vec = sv.iter()
+                .map(|s|builder.create_string(s.as_str()))
+                .collect::<Vec<$wip_offset<&str>>>();
+                builder.create_vector(strs_vec.as_slice())
+              });
+            }
+        }
+        ob_consts::OBXPropertyType_ByteVector => {
+            quote! {
+              let byte_vec_$offset = self.$name.as_ref().map(|bv| builder.create_vector(bv.as_slice()));
+            }
+        }
+        ob_consts::OBXPropertyType_String => {
+            quote! {
+              let str_$offset = self.$name.as_ref().map(|s| builder.create_string(s.as_str()));
+            }
+        }
+        _ => quote!(),
+    }
+}
+
+/// Fields with a PropertyConverter, their StoredValue creates strings and bytes
+/// before the table is started
+fn encode_converted_to_fb_unnested(p: &ModelProperty, offset: usize) -> Tokens<Rust> {
+    let name = &p.name;
+    let conv = p.as_converter();
+    let stored = p.as_stored_value();
+    if p.optional {
+        quote! {
+          let stored_$offset = self.$name.as_ref().map(|v| $stored::create(&$conv::to_stored(v), builder));
+        }
+    } else {
+        quote! {
+          let stored_$offset = $stored::create(&$conv::to_stored(&self.$name), builder);
+        }
+    }
+}
+
+fn encode_converted_flatten(p: &ModelProperty, offset: usize) -> Tokens<Rust> {
+    if p.optional {
+        quote! {
+          if let Some(s) = stored_$offset {
+            s.push(builder, $offset);
+          }
+        }
+    } else {
+        quote! {
+          stored_$offset.push(builder, $offset);
+        }
+    }
+}
+
+fn encode_optional_flatten(p: &ModelProperty, offset: usize) -> Tokens<Rust> {
+    let name = &p.name;
+    match p.type_field {
+        ob_consts::OBXPropertyType_StringVector => quote! {
+          if let Some(v) = vec_$offset {
+            builder.push_slot_always($offset, v);
+          }
+        },
+        ob_consts::OBXPropertyType_ByteVector => quote! {
+          if let Some(v) = byte_vec_$offset {
+            builder.push_slot_always($offset, v);
+          }
+        },
+        ob_consts::OBXPropertyType_String => quote! {
+          if let Some(v) = str_$offset {
+            builder.push_slot_always($offset, v);
+          }
+        },
+        ob_consts::OBXPropertyType_Char => quote! {
+          if let Some(v) = self.$name {
+            builder.push_slot_always::<u32>($offset, v as u32);
+          }
+        },
+        ob_consts::OBXPropertyType_Bool => quote! {
+          if let Some(v) = self.$name {
+            builder.push_slot_always::<bool>($offset, v);
+          }
+        },
+        ob_consts::OBXPropertyType_Float => quote! {
+          if let Some(v) = self.$name {
+            builder.push_slot_always::<f32>($offset, v);
+          }
+        },
+        ob_consts::OBXPropertyType_Double => quote! {
+          if let Some(v) = self.$name {
+            builder.push_slot_always::<f64>($offset, v);
+          }
+        },
+        ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+            let to_i64 = time_value_conversion(p.type_field, true);
+            quote! {
+              if let Some(v) = &self.$name {
+                builder.push_slot_always::<i64>($offset, $to_i64(v));
+              }
+            }
+        }
+        _ => {
+            let int_type = p.as_int_type();
+            quote! {
+              if let Some(v) = self.$name {
+                builder.push_slot_always::<$int_type>($offset, v);
+              }
+            }
+        }
+    }
+}
+
 impl CodeGenEntityExt for ModelEntity {
     fn get_id_property(&self) -> Option<&ModelProperty> {
         for p in self.properties.iter() {
@@ -180,6 +312,107 @@ impl CodeGenEntityExt for ModelEntity {
         }
     }
 
+    fn generate_relation_trait(&self) -> Tokens<Rust> {
+        let entity = &rust::import("self", &self.name);
+        let relation_trait = &rust::import("objectbox::traits", "RelationExt");
+
+        let mut methods = Tokens::<Rust>::new();
+
+        if !self.relations.is_empty() {
+            let schema_id = &rust::import("objectbox::c", "obx_schema_id");
+            let to_many_ext = &rust::import("objectbox::relations", "ToManyExt");
+            methods.append(quote! {
+              fn to_many_relations(&mut self) -> Vec<($schema_id, &mut dyn $to_many_ext)> {
+                vec![
+                  $(for r in &self.relations join (, ) => ($(r.id.get_id()), &mut self.$(r.name.as_str())))
+                ]
+              }
+            });
+        }
+
+        if !self.backlinks.is_empty() {
+            let obx_id = &rust::import("objectbox::c", "obx_id");
+            let attached_backlinks = self
+                .backlinks
+                .iter()
+                .map(|b| b.as_attached_backlink(quote!(&mut self.$(b.name.as_str())), quote!(id)));
+            methods.append(quote! {
+              fn attach_backlinks(&mut self, id: $obx_id) {
+                $(for b in attached_backlinks join () => $(b))
+              }
+            });
+        }
+
+        quote! {
+          impl $relation_trait for $entity {
+            $methods
+          }
+        }
+    }
+
+    fn generate_unique_trait(&self) -> Tokens<Rust> {
+        let entity = &rust::import("self", &self.name);
+        let unique_trait = &rust::import("objectbox::traits", "UniqueExt");
+        let ccb_fn = &rust::import("objectbox::query::traits", "create_condition_builder")
+            .with_module_alias("qtraits");
+        let eq_ext =
+            &rust::import("objectbox::query::traits", "EqExt").with_module_alias("qtraits");
+
+        // replaced on conflict, those don't fail the put, and null values never clash
+        let unique_props = self.properties.iter().filter(|p| {
+            let flags = p.flags.unwrap_or(0);
+            flags & ob_consts::OBXPropertyFlags_UNIQUE != 0
+                && flags & ob_consts::OBXPropertyFlags_UNIQUE_ON_CONFLICT_REPLACE == 0
+                && !p.optional
+        });
+
+        let conditions: Vec<Tokens<Rust>> = unique_props
+            .filter_map(|p| {
+                let name = p.name.as_str();
+                let value = match p.type_field {
+                    _ if p.converter.is_some() => {
+                        let conv = p.as_converter();
+                        quote!($conv::to_stored(&self.$name))
+                    }
+                    ob_consts::OBXPropertyType_Long
+                    | ob_consts::OBXPropertyType_Int
+                    | ob_consts::OBXPropertyType_Short
+                    | ob_consts::OBXPropertyType_Byte
+                    | ob_consts::OBXPropertyType_Char
+                    | ob_consts::OBXPropertyType_Bool => quote!(self.$name as i64),
+                    ob_consts::OBXPropertyType_String
+                    | ob_consts::OBXPropertyType_ByteVector => quote!(self.$name.clone()),
+                    ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+                        let to_i64 = time_value_conversion(p.type_field, true);
+                        quote!($to_i64(&self.$name))
+                    }
+                    _ => return None,
+                };
+                Some(quote! {
+                    ($(quoted(name)), $eq_ext::eq(&$ccb_fn::<Self, $(self.id.get_id()), $(p.id.get_id()), $(p.type_field)>(), $value))
+                })
+            })
+            .collect();
+
+        let mut methods = Tokens::<Rust>::new();
+        if !conditions.is_empty() {
+            let condition = &rust::import("objectbox::query::condition", "Condition");
+            methods.append(quote! {
+              fn unique_conditions(&self) -> Vec<(&'static str, $condition<Self>)> {
+                vec![
+                  $(for c in conditions join (, ) => $c)
+                ]
+              }
+            });
+        }
+
+        quote! {
+          impl $unique_trait for $entity {
+            $methods
+          }
+        }
+    }
+
     fn generate_fb_trait(&self) -> Tokens<Rust> {
         let entity = &rust::import("self", &self.name);
         let bridge_trait = &rust::import("objectbox::traits", "FBOBBridge");
@@ -189,7 +422,15 @@ impl CodeGenEntityExt for ModelEntity {
             .properties
             .iter()
             .enumerate()
-            .map(|(i, p)| encode_to_fb_unnested(p.type_field, i * 2 + 4, &p.name))
+            .map(|(i, p)| {
+                if p.converter.is_some() {
+                    encode_converted_to_fb_unnested(p, i * 2 + 4)
+                } else if p.optional {
+                    encode_optional_to_fb_unnested(p.type_field, i * 2 + 4, &p.name)
+                } else {
+                    encode_to_fb_unnested(p.type_field, i * 2 + 4, &p.name)
+                }
+            })
             .collect();
 
         let mut props_unsorted: Vec<(usize, Tokens<Rust>)> = self
@@ -197,10 +438,14 @@ impl CodeGenEntityExt for ModelEntity {
             .iter()
             .enumerate()
             .map(|(i, p)| {
-                (
-                    p.to_sorting_priority(),
-                    encode_flatten(p.type_field, p.flags, i * 2 + 4, &p.name),
-                )
+                let tokens = if p.converter.is_some() {
+                    encode_converted_flatten(p, i * 2 + 4)
+                } else if p.optional {
+                    encode_optional_flatten(p, i * 2 + 4)
+                } else {
+                    encode_flatten(p.type_field, p.flags, i * 2 + 4, &p.name)
+                };
+                (p.to_sorting_priority(), tokens)
             })
             .collect();
 
@@ -232,7 +477,19 @@ impl CodeGenEntityExt for ModelEntity {
         let destructured_props = self
             .properties
             .iter()
-            .map(|p| p.as_struct_property_default());
+            .map(|p| p.as_struct_property_default())
+            .chain(
+                self.relations
+                    .iter()
+                    .map(|r| r.name.as_str())
+                    .chain(self.backlinks.iter().map(|b| b.name.as_str()))
+                    .map(|name| quote!($name: Default::default())),
+            )
+            .chain(
+                self.transients
+                    .iter()
+                    .map(|t| t.as_struct_property_default()),
+            );
         let assigned_props = self
             .properties
             .iter()
@@ -248,18 +505,47 @@ impl CodeGenEntityExt for ModelEntity {
             }
         }
 
-        // TODO Store will be used for relations later
+        // standalone relations and backlinks are bound to the read object
+        let id_name = self
+            .get_id_property()
+            .map(|p| p.name.as_str())
+            .unwrap_or("id");
+        let to_many_ext = &rust::import("objectbox::relations", "ToManyExt");
+        let attached_relations: Vec<Tokens<Rust>> = self
+            .relations
+            .iter()
+            .map(|r| {
+                let name = r.name.as_str();
+                quote! {
+                    $to_many_ext::attach($name, $(id.as_str()), $(r.id.get_id()), *$id_name);
+                }
+            })
+            .chain(
+                self.backlinks
+                    .iter()
+                    .map(|b| b.as_attached_backlink(quote!($(b.name.as_str())), quote!(*$id_name))),
+            )
+            .collect();
+        let field_names = self
+            .properties
+            .iter()
+            .map(|p| p.name.as_str())
+            .chain(self.relations.iter().map(|r| r.name.as_str()))
+            .chain(self.backlinks.iter().map(|b| b.name.as_str()));
+
         quote! {
           impl $factory_helper<$entity> for $factory<$entity> {
             fn make(&self, table: &mut $fb_table) -> $entity {
               let mut object = self.new_entity();
               // destructure
               let $entity {
-                $(for p in &self.properties join (, ) => $(&p.name))
+                $(for name in field_names join (, ) => $name)
+                $(if !self.transients.is_empty() { , .. })
               } = &mut object;
               unsafe {
                 $(for p in assigned_props join () => $(p))
               }
+              $(for r in attached_relations join () => $(r))
               object
             }
 
@@ -276,6 +562,38 @@ impl CodeGenEntityExt for ModelEntity {
         }
     }
 
+    fn generate_ref_view(&self) -> Tokens<Rust> {
+        let fb_table = &rust::import("objectbox::flatbuffers", "Table");
+        let ref_trait = &rust::import("objectbox::traits", "RefExt");
+        let entity = &rust::import("self", &self.name);
+
+        let name = self.name.as_str();
+        let accessors = self
+            .properties
+            .iter()
+            .enumerate()
+            .map(|p| p.1.as_ref_accessor(p.0 * 2 + 4));
+
+        quote! {
+            #[derive(Clone, Copy)]
+            pub struct $(name)Ref<'tx> {
+                table: $fb_table<'tx>,
+            }
+
+            impl<'tx> $(name)Ref<'tx> {
+                $(for a in accessors join () => $(a))
+            }
+
+            impl $ref_trait for $entity {
+                type Ref<'tx> = $(name)Ref<'tx>;
+
+                unsafe fn make_ref(table: $fb_table<'_>) -> $(name)Ref<'_> {
+                    $(name)Ref { table }
+                }
+            }
+        }
+    }
+
     fn generate_query_trait_impls(&self) -> Tokens<Rust> {
         let entity = &rust::import("self", &self.name);
 
@@ -289,25 +607,70 @@ impl CodeGenEntityExt for ModelEntity {
             .iter()
             .map(|p| p.to_condition_factory_init_dyn(entity, self.id.get_id()));
 
+        let cf_relations = self
+            .relations
+            .iter()
+            .map(|r| r.to_condition_factory_struct_key_value(entity));
+
+        let cf_init_relations = self.relations.iter().map(|r| r.to_condition_factory_init());
+
+        let cf_backlinks = self
+            .backlinks
+            .iter()
+            .map(|b| b.to_condition_factory_struct_key_value(entity));
+
+        let cf_init_backlinks = self.backlinks.iter().map(|b| b.to_condition_factory_init());
+
         let name = self.name.as_str();
         let name_lower_case = self.name.to_ascii_lowercase();
 
-        let vec_type_field: Vec<ob_consts::OBXPropertyType> =
-            self.properties.iter().map(|p| p.type_field).collect();
+        // relations share the blanket of the long type, both date types the date one,
+        // converted properties have a blanket per converter
+        let vec_type_field: Vec<ob_consts::OBXPropertyType> = self
+            .properties
+            .iter()
+            .filter(|p| p.converter.is_none())
+            .map(|p| match p.type_field {
+                ob_consts::OBXPropertyType_Relation => ob_consts::OBXPropertyType_Long,
+                ob_consts::OBXPropertyType_DateNano => ob_consts::OBXPropertyType_Date,
+                t => t,
+            })
+            .collect();
         let hash_set =
             HashSet::<ob_consts::OBXPropertyType>::from_iter(vec_type_field.iter().cloned());
-        let impls = hash_set
+        let mut impls: Vec<Tokens<Rust>> = hash_set
             .iter()
-            .map(|t| prop_type_to_impl_blanket(*t, entity));
+            .map(|t| prop_type_to_impl_blanket(*t, entity))
+            .collect();
+        let converters = HashSet::<&str>::from_iter(
+            self.properties
+                .iter()
+                .filter_map(|p| p.converter.as_deref()),
+        );
+        impls.extend(
+            converters
+                .iter()
+                .map(|c| converter_to_impl_blanket(c, entity)),
+        );
+        impls.extend(
+            self.properties
+                .iter()
+                .filter(|p| p.converter.is_some())
+                .map(|p| p.as_stored_type_assertion(&self.name)),
+        );
 
         quote! {
             $(for p in impls join () => $(p))
             pub struct $(name)ConditionFactory {
                 $(for p in cf_props join () => $(p))
+                $(for r in cf_relations join () => $(r))
+                $(for b in cf_backlinks join () => $(b))
             }
             pub fn new_$(name_lower_case)_condition_factory() -> $(name)ConditionFactory {
                 $(name)ConditionFactory {
                   $(for p in cf_init_props join () => $(p))
+                  $(for r in cf_init_relations join () => $(r))
+                  $(for b in cf_init_backlinks join () => $(b))
                 }
             }
         }
@@ -337,10 +700,16 @@ fn generate_model_fn(model_info: &ModelInfo) -> Tokens<Rust> {
 
         props_unsorted.sort_by(|a, b| a.0.cmp(&b.0));
         let props: Vec<Tokens<Rust>> = props_unsorted.iter().map(|t| t.1.clone()).collect();
+        let relations: Vec<Tokens<Rust>> = e
+            .relations
+            .iter()
+            .map(|r| r.as_fluent_builder_invocation())
+            .collect();
 
         let quote = quote! {
           .entity($(quoted(entity_name)), $entity_id)
           $props
+          $relations
           .last_property_id($last_property_iduid)
         };
         tokens.append(quote);
@@ -362,6 +731,12 @@ fn generate_model_fn(model_info: &ModelInfo) -> Tokens<Rust> {
         quote!()
     };
 
+    let last_relation_id: Tokens<Rust> = if model_info.last_relation_id.is_empty() {
+        quote!()
+    } else {
+        quote! { .last_relation_id($(model_info.last_relation_id.as_comma_separated_str())) }
+    };
+
     let last_entity = model_info.entities.last().unwrap();
     let last_entity_id = last_entity.id.as_comma_separated_str();
 
@@ -371,15 +746,16 @@ fn generate_model_fn(model_info: &ModelInfo) -> Tokens<Rust> {
         $(tokens.clone())
         .last_entity_id($last_entity_id)
         $last_index_id
+        $last_relation_id
       }
     }
 }
 
 fn generate_factory_map_fn(model_info: &ModelInfo) -> Tokens<Rust> {
-    let any_map = &rust::import("objectbox::map", "AnyMap");
+    let factory_map = &rust::import("objectbox::traits", "FactoryMap");
     let factory = &rust::import("objectbox::traits", "Factory");
     let factory_helper = &rust::import("objectbox::traits", "EntityFactoryExt");
-    let rc = &rust::import("std::rc", "Rc");
+    let arc = &rust::import("std::sync", "Arc");
     let phantom = &rust::import("std::marker", "PhantomData");
 
     let tokens = &mut Tokens::<Rust>::new();
@@ -396,18 +772,18 @@ fn generate_factory_map_fn(model_info: &ModelInfo) -> Tokens<Rust> {
         }
         let entity_id_str = entity_id.as_str();
         let quote = quote! {
-          let f$(entity_id_str) = $rc::new($factory::<$entity> {
+          let f$(entity_id_str) = $arc::new($factory::<$entity> {
             phantom_data: $phantom,
             schema_id: $entity_id_str
-          }) as $rc<dyn $factory_helper<$entity>>;
+          }) as $arc<dyn $factory_helper<$entity>>;
           map.insert(f$entity_id_str);
         };
         tokens.append(quote);
     }
 
     quote! {
-      pub fn make_factory_map() -> $any_map {
-        let mut map = $any_map::new();
+      pub fn make_factory_map() -> $factory_map {
+        let mut map = $factory_map::new();
         $(tokens.clone())
         map
       }
@@ -420,8 +796,11 @@ impl CodeGenExt for ModelInfo {
 
         for e in self.entities.iter() {
             tokens.append(e.generate_id_trait());
+            tokens.append(e.generate_relation_trait());
+            tokens.append(e.generate_unique_trait());
             tokens.append(e.generate_fb_trait());
             tokens.append(e.generate_ob_trait());
+            tokens.append(e.generate_ref_view());
             tokens.append(e.generate_query_trait_impls());
         }
 
@@ -449,7 +828,7 @@ impl CodeGenExt for ModelInfo {
         // it seems that genco's code formatting is broken on stable
         let formatted = prettyplease::unparse(&syntax_tree);
 
-        if let Err(error) = fs::write(&dest_path, formatted.as_str()) {
+        if let Err(error) = write_if_changed(dest_path, formatted.as_str()) {
             panic!(
                 "There is a problem writing the generated rust code: {:?}",
                 error
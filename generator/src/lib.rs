@@ -103,6 +103,8 @@ trait EntityVecHelper {
     fn add_entities_to_model(&mut self, path_buffers: &[PathBuf]) -> &mut Self;
     fn assign_id_to_entities(&mut self) -> &mut Self;
     fn assign_id_to_indexables(&mut self) -> &mut Self;
+    fn assign_id_to_relations(&mut self) -> &mut Self;
+    fn resolve_backlinks(&mut self) -> &mut Self;
 }
 
 impl EntityVecHelper for Vec<ModelEntity> {
@@ -152,6 +154,80 @@ impl EntityVecHelper for Vec<ModelEntity> {
         }
         self
     }
+
+    /// Relation ids are unique over all entities, like index ids,
+    /// the targets are resolved by name, after the entity ids are assigned.
+    fn assign_id_to_relations(&mut self) -> &mut Self {
+        let entity_ids: HashMap<String, String> = self
+            .iter()
+            .map(|e| (e.name.clone(), e.id.clone()))
+            .collect();
+        let mut counter: u64 = 1;
+        let mut rng = rand::thread_rng();
+        for e in self.as_mut_slice() {
+            for r in e.relations.as_mut_slice() {
+                r.id = format!("{}:{}", counter, rng.gen::<u64>());
+                counter += 1;
+                if let Some(target_id) = entity_ids.get(&r.target) {
+                    r.target_id = target_id.clone();
+                } else {
+                    panic!(
+                        "The target {} of the relation {}.{} is not an entity",
+                        r.target, e.name, r.name
+                    );
+                }
+            }
+        }
+        self
+    }
+
+    /// Finds the ToOne property or the ToMany relation of the source, that points back,
+    /// either by the name given with `to`, or as the only one.
+    fn resolve_backlinks(&mut self) -> &mut Self {
+        let sources = self.clone();
+        for e in self.as_mut_slice() {
+            for b in e.backlinks.as_mut_slice() {
+                let source = if let Some(source) = sources.iter().find(|s| s.name == b.source) {
+                    source
+                } else {
+                    panic!(
+                        "The source {} of the backlink {}.{} is not an entity",
+                        b.source, e.name, b.name
+                    );
+                };
+                let is_candidate = |name: &str| b.to.as_ref().is_none_or(|to| to == name);
+                let mut candidates: Vec<(Option<String>, Option<String>)> = source
+                    .properties
+                    .iter()
+                    .filter(|p| p.relation_target.as_ref() == Some(&e.name))
+                    .filter(|p| is_candidate(&p.name))
+                    .map(|p| (Some(p.id.clone()), None))
+                    .collect();
+                candidates.extend(
+                    source
+                        .relations
+                        .iter()
+                        .filter(|r| r.target == e.name && is_candidate(&r.name))
+                        .map(|r| (None, Some(r.id.clone()))),
+                );
+                if candidates.len() != 1 {
+                    panic!(
+                        "The backlink {}.{} needs exactly one relation from {} to {}, found {}",
+                        e.name,
+                        b.name,
+                        source.name,
+                        e.name,
+                        candidates.len()
+                    );
+                }
+                let (property_id, relation_id) = candidates.remove(0);
+                b.source_id = source.id.clone();
+                b.property_id = property_id;
+                b.relation_id = relation_id;
+            }
+        }
+        self
+    }
 }
 
 mod code_gen;
@@ -169,9 +245,12 @@ pub fn generate_assets(out_path: &PathBuf, target_dir: &PathBuf) {
     let mut json_dest_path = target_dir.join("objectbox-model.json");
     let mut ob_dest_path = target_dir.join("objectbox_gen.rs");
     let mut model_has_changed = false;
+    // the recorded model, with its ids, as long as only the code generation changed
+    let mut recorded_model = None;
     // Exports everything a user needs from objectbox, fully generated
     if json_dest_path.exists() {
         let model_info_from_one_file = ModelInfo::from_json_file(&json_dest_path);
+        let mut recorded = model_info_from_one_file.clone();
 
         // Check difference in number of Entities
         if model_info_from_one_file.entities.len() != pbs.len() {
@@ -199,6 +278,25 @@ pub fn generate_assets(out_path: &PathBuf, target_dir: &PathBuf) {
                 }
                 model_has_changed |= count_properties_changed;
 
+                let count_relations_changed = e_new.relations.len() != e_before.relations.len();
+                if count_relations_changed {
+                    println!("cargo:warning=The number of relations ({}) have changed,\nconsider backing up and/or modifying or deleting objectbox-model.json", e_before.name);
+                }
+                model_has_changed |= count_relations_changed;
+
+                // a relation with another target is a new relation
+                let relations_changed = e_before.relations.iter().any(|r_before| {
+                    e_new
+                        .relations
+                        .iter()
+                        .find(|r| r.name == r_before.name)
+                        .is_none_or(|r_new| r_new.target != r_before.target)
+                });
+                if relations_changed && !count_relations_changed {
+                    println!("cargo:warning=The names or targets of relations ({}) have changed,\nconsider backing up and/or modifying or deleting objectbox-model.json", e_before.name);
+                }
+                model_has_changed |= relations_changed;
+
                 let mut p_map = HashMap::new();
                 e_new.properties.iter().for_each(|p| {
                     p_map.insert(p.name.as_str(), p);
@@ -206,16 +304,38 @@ pub fn generate_assets(out_path: &PathBuf, target_dir: &PathBuf) {
 
                 let mut flags_changed = false;
                 let mut types_changed = false;
+                // backlinks are resolved again, from the recorded relations
+                let backlink_sources = |e: &ModelEntity| {
+                    e.backlinks
+                        .iter()
+                        .map(|b| (b.name.clone(), b.source.clone(), b.to.clone()))
+                        .collect::<Vec<_>>()
+                };
+                let mut code_gen_changed = e_new.transients != e_before.transients
+                    || backlink_sources(e_new) != backlink_sources(&e_before);
                 for p_before in e_before.properties {
                     if let Some(p_new) = p_map.get(p_before.name.as_str()) {
                         flags_changed |= p_new.flags != p_before.flags;
-                        types_changed |= p_new.type_field != p_before.type_field;
+                        types_changed |= p_new.type_field != p_before.type_field
+                            || p_new.relation_target != p_before.relation_target;
                         model_has_changed |= flags_changed || types_changed;
+                        code_gen_changed |= p_new.optional != p_before.optional
+                            || p_new.time_type != p_before.time_type
+                            || p_new.converter != p_before.converter;
                     } else {
                         println!("cargo:warning=The names of properties ({}) have changed,\nconsider backing up and/or modifying or deleting objectbox-model.json", e_before.name);
                         names_changed |= true;
                     }
                 }
+                if code_gen_changed {
+                    if let Some(e) = recorded
+                        .entities
+                        .iter_mut()
+                        .find(|e| e.name == e_before.name)
+                    {
+                        e.update_code_gen_fields(e_new);
+                    }
+                }
             } else {
                 println!("cargo:warning=The names of entities have changed,\nconsider backing up and/or modifying or deleting objectbox-model.json");
                 names_changed |= true;
@@ -223,17 +343,21 @@ pub fn generate_assets(out_path: &PathBuf, target_dir: &PathBuf) {
         }
 
         model_has_changed |= names_changed;
+        recorded_model = Some(recorded);
     }
 
     if model_has_changed {
         json_dest_path.set_extension("json.new");
         ob_dest_path.set_extension("rs.new");
-    } else {
-        // do relatively inexpensive(?) checks to prevent generating files,
-        // and randomly generate uids and assign them
-        if ob_dest_path.exists() && json_dest_path.exists() {
-            return;
-        }
+    } else if let Some(model_info) = recorded_model.as_mut() {
+        // e.g. transients, backlinks, optional fields, time types or converters,
+        // the code is generated again for the recorded ids, both files only
+        // change if their content does
+        model_info.entities.resolve_backlinks();
+        model_info
+            .write_json(&json_dest_path)
+            .generate_code(&ob_dest_path);
+        return;
     }
 
     let mut entities = Vec::<ModelEntity>::new();
@@ -242,7 +366,9 @@ pub fn generate_assets(out_path: &PathBuf, target_dir: &PathBuf) {
     entities
         .add_entities_to_model(pbs.as_slice())
         .assign_id_to_entities()
-        .assign_id_to_indexables();
+        .assign_id_to_indexables()
+        .assign_id_to_relations()
+        .resolve_backlinks();
 
     ModelInfo::from_entities(entities.as_slice())
         .write_json(&json_dest_path)
@@ -4,14 +4,13 @@ use genco::quote;
 use genco::tokens::quoted;
 use genco::Tokens;
 use serde_derive::{Deserialize, Serialize};
-use serde_json::Value;
 
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::ob_consts;
-use crate::util::StringHelper;
+use crate::util::{write_if_changed, StringHelper};
 
 // TODO divide file into mod json::{info, entity, property}
 
@@ -61,6 +60,11 @@ impl ModelInfo {
         } else {
             last_property_with_index_id.id.to_string()
         };
+        let last_relation_id = entities
+            .iter()
+            .flat_map(|e| e.relations.iter())
+            .max_by_key(|r| split_id(&r.id).0.parse::<u64>().unwrap_or_default())
+            .map_or_else(String::new, |r| r.id.clone());
         ModelInfo {
         note1: String::from("KEEP THIS FILE! Check it into a version control system (VCS) like git."),
         note2: String::from("ObjectBox manages crucial IDs for your object model. See docs for details."),
@@ -68,7 +72,7 @@ impl ModelInfo {
         entities: entities.to_vec(), // rehydrate from slice to vec for JSON des, all of this without cloning
         last_entity_id: last_entity_id.to_string(),
         last_index_id: last_index_id.to_string(),
-        last_relation_id: String::from(""), // TODO
+        last_relation_id,
         last_sequence_id: String::from(""), // TODO
         model_version: 5,
         model_version_parser_minimum: 5,
@@ -80,9 +84,12 @@ impl ModelInfo {
       }
     }
 
-    pub(crate) fn write_json(&mut self, dest_path: &PathBuf) -> &mut Self {
-        if let Ok(json) = serde_json::to_string_pretty(self) {
-            match fs::write(&dest_path, json) {
+    /// The transients are left out, they are only kept in the .objectbox.info files
+    pub(crate) fn write_json(&mut self, dest_path: &Path) -> &mut Self {
+        let mut model = self.clone();
+        model.entities.iter_mut().for_each(|e| e.transients.clear());
+        if let Ok(json) = serde_json::to_string_pretty(&model) {
+            match write_if_changed(dest_path, &json) {
                 Err(error) => panic!("Problem writing the objectbox-model.json file: {:?}", error),
                 _ => {}
             }
@@ -108,10 +115,28 @@ pub struct ModelEntity {
     pub last_property_id: String,
     pub name: String,
     pub properties: Vec<ModelProperty>,
-    pub relations: Vec<Value>, // TODO
+    pub relations: Vec<ModelRelation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backlinks: Vec<ModelBacklink>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transients: Vec<ModelTransient>,
 }
 
 impl ModelEntity {
+    /// Takes the fields, that only the code generation uses, from the entity
+    /// read from its .objectbox.info file, the ids are kept.
+    pub(crate) fn update_code_gen_fields(&mut self, from: &ModelEntity) {
+        self.transients = from.transients.clone();
+        self.backlinks = from.backlinks.clone();
+        for p in self.properties.iter_mut() {
+            if let Some(p_from) = from.properties.iter().find(|f| f.name == p.name) {
+                p.optional = p_from.optional;
+                p.time_type = p_from.time_type.clone();
+                p.converter = p_from.converter.clone();
+            }
+        }
+    }
+
     pub fn write(&mut self) {
         if let Some(out_dir) = env::var_os("OUT_DIR") {
             let dest_path =
@@ -150,6 +175,138 @@ pub struct ModelProperty {
     pub flags: Option<ob_consts::OBXPropertyFlags>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation_target: Option<String>,
+    // Option<T> fields, only used by the code generation
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    // Rust type of Date and DateNano fields, only used by the code generation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_type: Option<String>,
+    // PropertyConverter of the field, only used by the code generation,
+    // the type is the one of its Stored type, Long, String or ByteVector
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub converter: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelRelation {
+    pub id: String, // iduid = "1:12341820347123498124"
+    pub name: String,
+    pub target_id: String,
+    // name of the target entity, to resolve target_id
+    pub target: String,
+}
+
+impl ModelRelation {
+    pub(crate) fn as_fluent_builder_invocation(&self) -> Tokens<Rust> {
+        let (id, uid) = split_id(&self.id);
+        let (target_id, target_uid) = split_id(&self.target_id);
+        quote! {
+            .relation($id, $uid, $target_id, $target_uid)
+        }
+    }
+
+    pub(crate) fn to_condition_factory_struct_key_value(
+        &self,
+        entity_name: &genco::lang::rust::Import,
+    ) -> Tokens<Rust> {
+        let link = &rust::import("objectbox::query::link", "Link");
+        let target = &rust::import("self", self.target.as_str());
+        quote! {
+            pub $(self.name.as_str())_link: $link<$entity_name, $target>,
+        }
+    }
+
+    pub(crate) fn to_condition_factory_init(&self) -> Tokens<Rust> {
+        let link = &rust::import("objectbox::query::link", "Link");
+        quote! {
+            $(self.name.as_str())_link: unsafe { $link::generated_to_many($(self.id.get_id())) },
+        }
+    }
+}
+
+/// `#[transient]` fields, not part of the stored model either,
+/// initialised by `default`, the path of a fn, or by Default::default()
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTransient {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+impl ModelTransient {
+    pub(crate) fn as_struct_property_default(&self) -> Tokens<Rust> {
+        let name = self.name.as_str();
+        match &self.default {
+            Some(path) => quote!($name: $(path.as_str())()),
+            None => quote!($name: Default::default()),
+        }
+    }
+}
+
+/// Not part of the stored model, resolved by the generator
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelBacklink {
+    pub name: String,
+    // name of the source entity, and of its relation field
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source_id: String,
+    // either a ToOne property, or a standalone relation of the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub property_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation_id: Option<String>,
+}
+
+impl ModelBacklink {
+    pub(crate) fn as_attached_backlink(
+        &self,
+        backlinks: Tokens<Rust>,
+        target_id: Tokens<Rust>,
+    ) -> Tokens<Rust> {
+        let backlinks_ext = &rust::import("objectbox::relations", "BacklinksExt");
+        let backlink_source = &rust::import("objectbox::relations", "BacklinkSource");
+        let via = match (&self.property_id, &self.relation_id) {
+            (Some(p), _) => quote!($backlink_source::Property($(p.get_id()))),
+            (_, Some(r)) => quote!($backlink_source::Relation($(r.get_id()))),
+            _ => panic!("The backlink {} was not resolved", self.name),
+        };
+        quote! {
+            $backlinks_ext::attach($backlinks, $(self.source_id.get_id()), $via, $target_id);
+        }
+    }
+
+    pub(crate) fn to_condition_factory_struct_key_value(
+        &self,
+        entity_name: &genco::lang::rust::Import,
+    ) -> Tokens<Rust> {
+        let link = &rust::import("objectbox::query::link", "Link");
+        let source = &rust::import("self", self.source.as_str());
+        quote! {
+            pub $(self.name.as_str())_link: $link<$entity_name, $source>,
+        }
+    }
+
+    pub(crate) fn to_condition_factory_init(&self) -> Tokens<Rust> {
+        let link = &rust::import("objectbox::query::link", "Link");
+        let link_init = match (&self.property_id, &self.relation_id) {
+            (Some(p), _) => {
+                quote!($link::generated_to_one($(self.source_id.get_id()), $(p.get_id())))
+            }
+            (_, Some(r)) => quote!($link::generated_to_many($(r.get_id()))),
+            _ => panic!("The backlink {} was not resolved", self.name),
+        };
+        quote! {
+            $(self.name.as_str())_link: unsafe { $link_init }.backlink(),
+        }
+    }
 }
 
 fn split_id(input: &str) -> (&str, &str) {
@@ -172,15 +329,28 @@ impl ModelProperty {
         };
         if let Some(ii) = &self.index_id {
             let (id, uid) = split_id(&ii);
-            q.extend(quote! {
-                .property_index($id, $uid)
-            });
+            // the index of a relation is declared with the relation
+            if let Some(target) = &self.relation_target {
+                q.extend(quote! {
+                    .property_relation($(quoted(target.as_str())), $id, $uid)
+                });
+            } else {
+                q.extend(quote! {
+                    .property_index($id, $uid)
+                });
+            }
         }
         q
     }
 
     pub(crate) fn as_struct_property_default(&self) -> Tokens<Rust> {
         let name = &self.name;
+        if self.optional {
+            return quote!($name: None);
+        }
+        if let Some(conv) = self.as_converter() {
+            return quote!($name: $conv::from_stored(Default::default()));
+        }
         match self.type_field {
             ob_consts::OBXPropertyType_StringVector => quote! {
                 $name: Vec::<String>::new()
@@ -203,6 +373,16 @@ impl ModelProperty {
             ob_consts::OBXPropertyType_Double => quote! {
                 $name: 0.0
             },
+            ob_consts::OBXPropertyType_Relation => quote! {
+                $name: Default::default()
+            },
+            // the epoch
+            ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+                let from_i64 = time_value_conversion(self.type_field, false);
+                quote! {
+                    $name: $from_i64(0)
+                }
+            }
             // rest of the integer types
             _ => quote! {
                 $name: 0
@@ -227,6 +407,15 @@ impl ModelProperty {
         }
 
         let name = &self.name;
+        if self.optional {
+            return self.as_assigned_optional_property(offset);
+        }
+        if let Some(conv) = self.as_converter() {
+            let read = self.as_converted_read(offset, quote!(table));
+            return quote! {
+                *$name = $read.unwrap_or_else(|| $conv::from_stored(Default::default()));
+            };
+        }
         match self.type_field {
             ob_consts::OBXPropertyType_StringVector => quote! {
                 let fb_vec_$name = table.get::<$fuo<$fvec<$fuo<&str>>>>($offset, None);
@@ -264,38 +453,295 @@ impl ModelProperty {
             ob_consts::OBXPropertyType_Double => quote! {
                 *$name = table.get::<f64>($offset, Some(0.0)).unwrap();
             },
+            ob_consts::OBXPropertyType_Relation => quote! {
+                $name.set_target_id(table.get::<u64>($offset, Some(0)).unwrap());
+            },
+            ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+                let from_i64 = time_value_conversion(self.type_field, false);
+                quote! {
+                    *$name = $from_i64(table.get::<i64>($offset, Some(0)).unwrap());
+                }
+            }
             // rest of the integer types
             _ => {
-                let unsigned_flag = match self.flags {
-                    Some(f) => f,
-                    _ => 0,
-                };
-                let sign: Tokens<Rust> = if (unsigned_flag & ob_consts::OBXPropertyFlags_UNSIGNED)
-                    == ob_consts::OBXPropertyFlags_UNSIGNED
-                {
-                    quote!(u)
-                } else {
-                    quote!(i)
-                };
+                let int_type = self.as_int_type();
+                quote! {
+                    *$name = table.get::<$int_type>($offset, Some(0)).unwrap();
+                }
+            }
+        }
+    }
+
+    /// An absent value is read as None
+    fn as_assigned_optional_property(&self, offset: usize) -> Tokens<Rust> {
+        let fuo = &rust::import("objectbox::flatbuffers", "ForwardsUOffset");
+        let fvec = &rust::import("objectbox::flatbuffers", "Vector");
+
+        let name = &self.name;
+        if self.converter.is_some() {
+            let read = self.as_converted_read(offset, quote!(table));
+            return quote! {
+                *$name = $read;
+            };
+        }
+        match self.type_field {
+            ob_consts::OBXPropertyType_StringVector => quote! {
+                *$name = table.get::<$fuo<$fvec<$fuo<&str>>>>($offset, None)
+                    .map(|sv| sv.iter().map(|s|s.to_string()).collect());
+            },
+            ob_consts::OBXPropertyType_ByteVector => quote! {
+                *$name = table.get::<$fuo<$fvec<u8>>>($offset, None).map(|bv| bv.bytes().to_vec());
+            },
+            ob_consts::OBXPropertyType_String => quote! {
+                *$name = table.get::<$fuo<&str>>($offset, None).map(|s| s.to_string());
+            },
+            ob_consts::OBXPropertyType_Char => quote! {
+                *$name = table.get::<u32>($offset, None).and_then(std::char::from_u32);
+            },
+            ob_consts::OBXPropertyType_Bool => quote! {
+                *$name = table.get::<bool>($offset, None);
+            },
+            ob_consts::OBXPropertyType_Float => quote! {
+                *$name = table.get::<f32>($offset, None);
+            },
+            ob_consts::OBXPropertyType_Double => quote! {
+                *$name = table.get::<f64>($offset, None);
+            },
+            ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+                let from_i64 = time_value_conversion(self.type_field, false);
+                quote! {
+                    *$name = table.get::<i64>($offset, None).map($from_i64);
+                }
+            }
+            _ => {
+                let int_type = self.as_int_type();
+                quote! {
+                    *$name = table.get::<$int_type>($offset, None);
+                }
+            }
+        }
+    }
+
+    /// `<MyConverter as PropertyConverter>`, of a field with a converter
+    pub(crate) fn as_converter(&self) -> Option<Tokens<Rust>> {
+        let property_converter = &rust::import("objectbox::traits", "PropertyConverter");
+        let conv = self.converter.as_deref()?;
+        Some(quote!(<$conv as $property_converter>))
+    }
+
+    /// `<<MyConverter as PropertyConverter>::Stored as StoredValue>`
+    pub(crate) fn as_stored_value(&self) -> Tokens<Rust> {
+        let stored_value =
+            &rust::import("objectbox::query::traits", "StoredValue").with_module_alias("qtraits");
+        let conv = self.as_converter();
+        quote!(<$conv::Stored as $stored_value>)
+    }
+
+    /// Fails the build, if the Stored type of the converter isn't the recorded type
+    pub(crate) fn as_stored_type_assertion(&self, entity_name: &str) -> Tokens<Rust> {
+        let stored = self.as_stored_value();
+        let message = format!(
+            "The Stored type of {} doesn't match #[property(type)] of {}.{}, expected 'long', 'string' or 'bytes'",
+            self.converter.as_deref().unwrap_or_default(),
+            entity_name,
+            self.name
+        );
+        quote! {
+            const _: () = assert!($stored::PROPERTY_TYPE == $(self.type_field), $(quoted(message)));
+        }
+    }
+
+    /// Reads the stored value of a field with a converter, None if it's absent
+    fn as_converted_read(&self, offset: usize, table: Tokens<Rust>) -> Tokens<Rust> {
+        let conv = self.as_converter();
+        let stored = self.as_stored_value();
+        quote!($stored::read(&$table, $offset).map($conv::from_stored))
+    }
+
+    pub(crate) fn as_int_type(&self) -> Tokens<Rust> {
+        let unsigned_flag = match self.flags {
+            Some(f) => f,
+            _ => 0,
+        };
+        let sign: Tokens<Rust> = if (unsigned_flag & ob_consts::OBXPropertyFlags_UNSIGNED)
+            == ob_consts::OBXPropertyFlags_UNSIGNED
+        {
+            quote!(u)
+        } else {
+            quote!(i)
+        };
+
+        let bits: Tokens<Rust> = match self.type_field {
+            ob_consts::OBXPropertyType_Byte => quote!(8),
+            ob_consts::OBXPropertyType_Short => quote!(16),
+            ob_consts::OBXPropertyType_Int => quote!(32),
+            ob_consts::OBXPropertyType_Long => quote!(64),
+            _ => panic!("Unknown OBXPropertyType"),
+        };
+        quote!($sign$bits)
+    }
+
+    /// The accessor of the borrowed view, reads the field from the table
+    pub(crate) fn as_ref_accessor(&self, offset: usize) -> Tokens<Rust> {
+        let fuo = &rust::import("objectbox::flatbuffers", "ForwardsUOffset");
+        let fvec = &rust::import("objectbox::flatbuffers", "Vector");
 
-                let bits: Tokens<Rust> = match self.type_field {
-                    ob_consts::OBXPropertyType_Byte => quote!(8),
-                    ob_consts::OBXPropertyType_Short => quote!(16),
-                    ob_consts::OBXPropertyType_Int => quote!(32),
-                    ob_consts::OBXPropertyType_Long => quote!(64),
-                    _ => panic!("Unknown OBXPropertyType"),
+        let name = &self.name;
+        if let Some(f) = self.flags {
+            if f == (ob_consts::OBXPropertyFlags_ID_SELF_ASSIGNABLE
+                | ob_consts::OBXPropertyFlags_ID)
+            {
+                return quote! {
+                    pub fn $name(&self) -> u64 {
+                        unsafe { self.table.get::<u64>($offset, Some(0)).unwrap() }
+                    }
                 };
+            }
+        }
+
+        if self.optional {
+            return self.as_optional_ref_accessor(offset);
+        }
+        if let Some(conv) = self.as_converter() {
+            let read = self.as_converted_read(offset, quote!(self.table));
+            return quote! {
+                pub fn $name(&self) -> $(conv.clone())::Value {
+                    unsafe { $read }.unwrap_or_else(|| $conv::from_stored(Default::default()))
+                }
+            };
+        }
+
+        match self.type_field {
+            ob_consts::OBXPropertyType_StringVector => quote! {
+                pub fn $name(&self) -> $fvec<'tx, $fuo<&'tx str>> {
+                    unsafe { self.table.get::<$fuo<$fvec<$fuo<&str>>>>($offset, None).unwrap_or_default() }
+                }
+            },
+            ob_consts::OBXPropertyType_ByteVector => quote! {
+                pub fn $name(&self) -> &'tx [u8] {
+                    unsafe { self.table.get::<$fuo<$fvec<u8>>>($offset, None).map_or(&[], |v| v.bytes()) }
+                }
+            },
+            ob_consts::OBXPropertyType_String => quote! {
+                pub fn $name(&self) -> &'tx str {
+                    unsafe { self.table.get::<$fuo<&str>>($offset, None).unwrap_or_default() }
+                }
+            },
+            ob_consts::OBXPropertyType_Char => quote! {
+                pub fn $name(&self) -> char {
+                    let u = unsafe { self.table.get::<u32>($offset, Some(0)).unwrap() };
+                    std::char::from_u32(u).unwrap_or_default()
+                }
+            },
+            ob_consts::OBXPropertyType_Bool => quote! {
+                pub fn $name(&self) -> bool {
+                    unsafe { self.table.get::<bool>($offset, Some(false)).unwrap() }
+                }
+            },
+            ob_consts::OBXPropertyType_Float => quote! {
+                pub fn $name(&self) -> f32 {
+                    unsafe { self.table.get::<f32>($offset, Some(0.0)).unwrap() }
+                }
+            },
+            ob_consts::OBXPropertyType_Double => quote! {
+                pub fn $name(&self) -> f64 {
+                    unsafe { self.table.get::<f64>($offset, Some(0.0)).unwrap() }
+                }
+            },
+            // the id of the target
+            ob_consts::OBXPropertyType_Relation => quote! {
+                pub fn $(name)_id(&self) -> u64 {
+                    unsafe { self.table.get::<u64>($offset, Some(0)).unwrap() }
+                }
+            },
+            ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+                let from_i64 = time_value_conversion(self.type_field, false);
+                let time_type = self.time_type.as_deref().unwrap_or_default();
                 quote! {
-                    *$name = table.get::<$sign$bits>($offset, Some(0)).unwrap();
+                    pub fn $name(&self) -> $time_type {
+                        $from_i64(unsafe { self.table.get::<i64>($offset, Some(0)).unwrap() })
+                    }
+                }
+            }
+            _ => {
+                let int_type = self.as_int_type();
+                quote! {
+                    pub fn $name(&self) -> $(int_type.clone()) {
+                        unsafe { self.table.get::<$int_type>($offset, Some(0)).unwrap() }
+                    }
                 }
             }
         }
     }
 
+    fn as_optional_ref_accessor(&self, offset: usize) -> Tokens<Rust> {
+        let fuo = &rust::import("objectbox::flatbuffers", "ForwardsUOffset");
+        let fvec = &rust::import("objectbox::flatbuffers", "Vector");
+
+        let name = &self.name;
+        if let Some(conv) = self.as_converter() {
+            let read = self.as_converted_read(offset, quote!(self.table));
+            return quote! {
+                pub fn $name(&self) -> Option<$conv::Value> {
+                    unsafe { $read }
+                }
+            };
+        }
+        let (value_type, read): (Tokens<Rust>, Tokens<Rust>) = match self.type_field {
+            ob_consts::OBXPropertyType_StringVector => (
+                quote!($fvec<'tx, $fuo<&'tx str>>),
+                quote!(self.table.get::<$fuo<$fvec<$fuo<&str>>>>($offset, None)),
+            ),
+            ob_consts::OBXPropertyType_ByteVector => (
+                quote!(&'tx [u8]),
+                quote!(self.table.get::<$fuo<$fvec<u8>>>($offset, None).map(|v| v.bytes())),
+            ),
+            ob_consts::OBXPropertyType_String => (
+                quote!(&'tx str),
+                quote!(self.table.get::<$fuo<&str>>($offset, None)),
+            ),
+            ob_consts::OBXPropertyType_Char => (
+                quote!(char),
+                quote!(self.table.get::<u32>($offset, None).and_then(std::char::from_u32)),
+            ),
+            ob_consts::OBXPropertyType_Bool => {
+                (quote!(bool), quote!(self.table.get::<bool>($offset, None)))
+            }
+            ob_consts::OBXPropertyType_Float => {
+                (quote!(f32), quote!(self.table.get::<f32>($offset, None)))
+            }
+            ob_consts::OBXPropertyType_Double => {
+                (quote!(f64), quote!(self.table.get::<f64>($offset, None)))
+            }
+            ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+                let from_i64 = time_value_conversion(self.type_field, false);
+                (
+                    quote!($(self.time_type.as_deref().unwrap_or_default())),
+                    quote!(self.table.get::<i64>($offset, None).map($from_i64)),
+                )
+            }
+            _ => {
+                let int_type = self.as_int_type();
+                (
+                    int_type.clone(),
+                    quote!(self.table.get::<$int_type>($offset, None)),
+                )
+            }
+        };
+        quote! {
+            pub fn $name(&self) -> Option<$value_type> {
+                unsafe { $read }
+            }
+        }
+    }
+
     pub(crate) fn to_sorting_priority(&self) -> usize {
         match self.type_field {
             ob_consts::OBXPropertyType_Double => 1,
             ob_consts::OBXPropertyType_Long => 1,
+            ob_consts::OBXPropertyType_Relation => 1,
+            ob_consts::OBXPropertyType_Date => 1,
+            ob_consts::OBXPropertyType_DateNano => 1,
             ob_consts::OBXPropertyType_StringVector => 2,
             ob_consts::OBXPropertyType_ByteVector => 3,
             ob_consts::OBXPropertyType_String => 4,
@@ -333,7 +779,17 @@ impl ModelProperty {
             &rust::import("objectbox::query::traits", "VecU8Blanket").with_module_alias("qtraits");
         let type_string =
             &rust::import("objectbox::query::traits", "StringBlanket").with_module_alias("qtraits");
+        let type_date =
+            &rust::import("objectbox::query::traits", "DateBlanket").with_module_alias("qtraits");
         let name = &self.name;
+        // conditions on values of the field type
+        if let Some(conv) = self.converter.as_deref() {
+            let type_converted = &rust::import("objectbox::query::traits", "ConvertedBlanket")
+                .with_module_alias("qtraits");
+            return quote! {
+                pub $name: Box<dyn $type_converted<$entity_name, $conv>>,
+            };
+        }
         match self.type_field {
             ob_consts::OBXPropertyType_Double => quote! {
                 pub $name: Box<dyn $type_double<$entity_name>>,
@@ -341,6 +797,16 @@ impl ModelProperty {
             ob_consts::OBXPropertyType_Long => quote! {
                 pub $name: Box<dyn $type_long<$entity_name>>,
             },
+            // conditions on the target id, and a link for conditions on the target
+            ob_consts::OBXPropertyType_Relation => {
+                let link = &rust::import("objectbox::query::link", "Link");
+                let target =
+                    &rust::import("self", self.relation_target.as_deref().unwrap_or_default());
+                quote! {
+                    pub $name: Box<dyn $type_long<$entity_name>>,
+                    pub $(name)_link: $link<$entity_name, $target>,
+                }
+            }
             ob_consts::OBXPropertyType_ByteVector => quote! {
                 pub $name: Box<dyn $type_byte_vec<$entity_name>>,
             },
@@ -365,6 +831,9 @@ impl ModelProperty {
             ob_consts::OBXPropertyType_Byte => quote! {
                 pub $name: Box<dyn $type_byte<$entity_name>>,
             },
+            ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => quote! {
+                pub $name: Box<dyn $type_date<$entity_name>>,
+            },
             _ => quote!(), // TODO refine this for the remaining types, no support for now
         }
     }
@@ -390,14 +859,51 @@ impl ModelProperty {
             | ob_consts::OBXPropertyType_Char
             | ob_consts::OBXPropertyType_Short
             | ob_consts::OBXPropertyType_Bool
-            | ob_consts::OBXPropertyType_Byte => quote! {
+            | ob_consts::OBXPropertyType_Byte
+            | ob_consts::OBXPropertyType_Date
+            | ob_consts::OBXPropertyType_DateNano => quote! {
                 $name: Box::new($ccb_fn::<$entity_name, $entity_id, $(property_id), $(self.type_field)>()),
             },
+            ob_consts::OBXPropertyType_Relation => {
+                let link = &rust::import("objectbox::query::link", "Link");
+                quote! {
+                    $name: Box::new($ccb_fn::<$entity_name, $(entity_id.clone()), $(property_id), $(self.type_field)>()),
+                    $(name)_link: unsafe { $link::generated_to_one($entity_id, $(property_id)) },
+                }
+            }
             _ => quote!(), // TODO refine this for the remaining types, no support for now
         }
     }
 }
 
+/// TimeValue::{to,from}_millis for Date, {to,from}_nanos for DateNano properties
+pub(crate) fn time_value_conversion(
+    type_field: ob_consts::OBXPropertyType,
+    to_i64: bool,
+) -> Tokens<Rust> {
+    let time_value = &rust::import("objectbox::time", "TimeValue");
+    match (type_field == ob_consts::OBXPropertyType_DateNano, to_i64) {
+        (false, true) => quote!($time_value::to_millis),
+        (false, false) => quote!($time_value::from_millis),
+        (true, true) => quote!($time_value::to_nanos),
+        (true, false) => quote!($time_value::from_nanos),
+    }
+}
+
+/// The blanket of the conditions on the properties with the given converter
+pub(crate) fn converter_to_impl_blanket(
+    converter: &str,
+    entity_name: &genco::lang::rust::Import,
+) -> Tokens<Rust> {
+    let impl_converted =
+        &rust::import("objectbox::query::traits", "ConvertedBlanket").with_module_alias("qtraits");
+    let cb =
+        &rust::import("objectbox::query::traits", "ConditionBuilder").with_module_alias("qtraits");
+    quote! {
+        impl $impl_converted<$entity_name, $converter> for $cb<$entity_name> {}
+    }
+}
+
 /// Use unique set of OBXPropertyType to generate the required blankets
 pub(crate) fn prop_type_to_impl_blanket(
     type_field: ob_consts::OBXPropertyType,
@@ -423,6 +929,8 @@ pub(crate) fn prop_type_to_impl_blanket(
         &rust::import("objectbox::query::traits", "VecU8Blanket").with_module_alias("qtraits");
     let impl_string =
         &rust::import("objectbox::query::traits", "StringBlanket").with_module_alias("qtraits");
+    let impl_date =
+        &rust::import("objectbox::query::traits", "DateBlanket").with_module_alias("qtraits");
 
     let cb =
         &rust::import("objectbox::query::traits", "ConditionBuilder").with_module_alias("qtraits");
@@ -462,6 +970,11 @@ pub(crate) fn prop_type_to_impl_blanket(
                 impl $impl_char<$entity_name> for $cb<$entity_name> {}
             }
         }
+        ob_consts::OBXPropertyType_Date | ob_consts::OBXPropertyType_DateNano => {
+            quote! {
+                impl $impl_date<$entity_name> for $cb<$entity_name> {}
+            }
+        }
         ob_consts::OBXPropertyType_Short => {
             quote! {
                 impl $impl_short<$entity_name> for $cb<$entity_name> {}
@@ -493,6 +1006,10 @@ mod tests {
             type_field: 3,
             flags: Some(0),
             index_id: Some("2:3".to_string()),
+            relation_target: None,
+            optional: false,
+            time_type: None,
+            converter: None,
         }
     }
 
@@ -1,5 +1,15 @@
+use std::{fs, io, path::Path};
+
 use genco::{prelude::Rust, quote, Tokens};
 
+/// Leaves the file alone if its content is the same, so its timestamp doesn't change
+pub(crate) fn write_if_changed(path: &Path, content: &str) -> io::Result<()> {
+    if fs::read_to_string(path).is_ok_and(|old| old == content) {
+        return Ok(());
+    }
+    fs::write(path, content)
+}
+
 pub(crate) trait StringHelper {
     fn as_comma_separated_str(&self) -> Tokens<Rust>;
     fn get_id(&self) -> Tokens<Rust>;
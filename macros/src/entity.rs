@@ -1,27 +1,94 @@
 use objectbox_generator::{id, model_json};
-use syn::{punctuated::Pair, DeriveInput};
+use syn::DeriveInput;
 
+use crate::path_visitor::get_type_arg_of;
 use crate::property::Property;
 
 // TODO see if uid type = u64 can be parameterized with generics e.g. 0x... 0b... etc.
 // TODO see how generics work with this e.g. "struct Gen<T> { field: T }"
-// TODO see how fields with Option<T> type, that default to None, and how store deals with this
 // TODO check if another attribute macro can mess with our attribute, otherwise panic if another attribute is present
 #[derive(Debug)]
 pub(crate) struct Entity {
     name: String,
     id: id::IdUid,
     fields: Vec<Property>,
+    relations: Vec<model_json::ModelRelation>,
+    backlinks: Vec<model_json::ModelBacklink>,
+    transients: Vec<model_json::ModelTransient>,
 }
 
 fn warn_transient(entity_name: &str, field_name: &str) {
     panic!(
-        "Error: There is a field {}::{} with an unsupported type.",
+        "Error: There is a field {}::{} with an unsupported type, mark it #[transient] to skip it.",
         entity_name, field_name
     );
     // println!("Warning: {}.{} will be considered as a transient", entity_name, field_name);
 }
 
+/// `#[transient]` or `#[transient(default = "path::to_fn")]`, left out of the model
+fn transient_from_syn_field(field: &syn::Field) -> Option<model_json::ModelTransient> {
+    let name = field.ident.as_ref()?.to_string();
+    let attr = field.attrs.iter().find(|a| a.path.is_ident("transient"))?;
+
+    let mut default = None;
+    if let Ok(syn::Meta::List(meta_list)) = attr.parse_meta() {
+        for nm in meta_list.nested.iter() {
+            if let syn::NestedMeta::Meta(syn::Meta::NameValue(mnv)) = nm {
+                if let (true, syn::Lit::Str(s)) = (mnv.path.is_ident("default"), &mnv.lit) {
+                    default = Some(s.value());
+                }
+            }
+        }
+    }
+
+    Some(model_json::ModelTransient { name, default })
+}
+
+/// Standalone relations, i.e. `ToMany<Target>`, are not stored as properties.
+/// The ids are assigned by the generator.
+fn relation_from_syn_field(field: &syn::Field) -> Option<model_json::ModelRelation> {
+    let name = field.ident.as_ref()?.to_string();
+    let target = get_type_arg_of(&field.ty, "ToMany")?;
+    Some(model_json::ModelRelation {
+        id: String::new(),
+        name,
+        target_id: String::new(),
+        target: target.to_string(),
+    })
+}
+
+/// `#[backlink(to = "field")]` on a `Backlinks<Source>`, not stored either.
+/// Without `to`, the source entity must have exactly one relation to this entity.
+fn backlink_from_syn_field(field: &syn::Field) -> Option<model_json::ModelBacklink> {
+    let name = field.ident.as_ref()?.to_string();
+    let attr = field.attrs.iter().find(|a| a.path.is_ident("backlink"));
+    let source = match get_type_arg_of(&field.ty, "Backlinks") {
+        Some(source) => source.to_string(),
+        None if attr.is_some() => {
+            panic!("#[backlink] requires a Backlinks<Source> field: {}", name)
+        }
+        None => return None,
+    };
+
+    let mut to = None;
+    if let Some(Ok(syn::Meta::List(meta_list))) = attr.map(|a| a.parse_meta()) {
+        for nm in meta_list.nested.iter() {
+            if let syn::NestedMeta::Meta(syn::Meta::NameValue(mnv)) = nm {
+                if let (true, syn::Lit::Str(s)) = (mnv.path.is_ident("to"), &mnv.lit) {
+                    to = Some(s.value());
+                }
+            }
+        }
+    }
+
+    Some(model_json::ModelBacklink {
+        name,
+        source,
+        to,
+        ..Default::default()
+    })
+}
+
 impl Entity {
     /// Unnamed fields are ignored, e.g. nested anonymous unions / structs, like in C.
     pub(crate) fn from_entity_name_and_fields(id: id::IdUid, derive_input: DeriveInput) -> Entity {
@@ -29,36 +96,33 @@ impl Entity {
             name: derive_input.ident.to_string(),
             id: id,
             fields: Vec::<Property>::new(),
+            relations: Vec::new(),
+            backlinks: Vec::new(),
+            transients: Vec::new(),
         };
         let Entity {
             name: entity_name,
             id: _,
             fields,
+            relations,
+            backlinks,
+            transients,
         } = &mut entity;
         if let syn::Data::Struct(ds) = derive_input.data {
             match ds.fields {
                 syn::Fields::Named(fields_named) => {
-                    fields_named.named.pairs().for_each(|p| {
-                        match p {
-                            Pair::Punctuated(t, _) => {
-                                // TODO check for attribute: #[transient]
-                                if let Some(f) = Property::from_syn_field(t) {
-                                    if f.field_type == 0 {
-                                        warn_transient(&entity_name, &f.name);
-                                    } else {
-                                        fields.push(f);
-                                    }
-                                }
-                            }
-                            Pair::End(t) => {
-                                // TODO check for attribute: #[transient]
-                                if let Some(f) = Property::from_syn_field(t) {
-                                    if f.field_type == 0 {
-                                        warn_transient(&entity_name, &f.name);
-                                    } else {
-                                        fields.push(f);
-                                    }
-                                }
+                    fields_named.named.iter().for_each(|t| {
+                        if let Some(tr) = transient_from_syn_field(t) {
+                            transients.push(tr);
+                        } else if let Some(r) = relation_from_syn_field(t) {
+                            relations.push(r);
+                        } else if let Some(b) = backlink_from_syn_field(t) {
+                            backlinks.push(b);
+                        } else if let Some(f) = Property::from_syn_field(t) {
+                            if f.field_type == 0 {
+                                warn_transient(&entity_name, &f.name);
+                            } else {
+                                fields.push(f);
                             }
                         }
                     });
@@ -98,6 +162,10 @@ impl Entity {
                 type_field: f.field_type,
                 flags,
                 index_id,
+                relation_target: f.relation_target.clone(),
+                optional: f.optional,
+                time_type: f.time_type.clone(),
+                converter: f.converter.clone(),
             };
             v.push(p);
         }
@@ -110,9 +178,11 @@ impl Entity {
             last_property_id: self.get_last_property_id().to_string(),
             name: self.name.clone(),
             properties: self.get_properties(),
-            relations: Vec::new(), // TODO
-                                   // path: None,
-                                   // TODO see flags
+            relations: self.relations.clone(),
+            backlinks: self.backlinks.clone(),
+            transients: self.transients.clone(),
+            // path: None,
+            // TODO see flags
         }
     }
 }
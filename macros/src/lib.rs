@@ -147,7 +147,7 @@ pub fn index(_attribute: TokenStream, input: TokenStream) -> TokenStream {
     input
 }
 
-/// Accepts 'uid'
+/// Accepts 'uid', and 'on_conflict' = "fail" (default) or "replace"
 #[proc_macro_attribute]
 pub fn unique(_attribute: TokenStream, input: TokenStream) -> TokenStream {
     input
@@ -159,13 +159,18 @@ pub fn backlink(_attribute: TokenStream, input: TokenStream) -> TokenStream {
     input
 }
 
-/// No required params
+/// Accepts 'default', the path of a fn that initialises the field,
+/// otherwise it's initialised with Default::default()
 #[proc_macro_attribute]
 pub fn transient(_attribute: TokenStream, input: TokenStream) -> TokenStream {
     input
 }
 
-/// Accepts 'type' and 'uid'
+/// Accepts 'type' and 'uid'.
+/// DateTime<Utc>, NaiveDateTime and SystemTime fields are stored as "date" (milliseconds),
+/// or as "date_nano" with `#[property(type = "date_nano")]`
+/// Other field types need 'convert', the path of a PropertyConverter,
+/// stored as "long" by default, or as "string" or "bytes", matching its Stored type
 #[proc_macro_attribute]
 pub fn property(_attribute: TokenStream, input: TokenStream) -> TokenStream {
     input
@@ -32,6 +32,44 @@ pub fn get_idents_from_path(path: &Type) -> Vec<Ident> {
     visitor.idents
 }
 
+/// The last segment of the type argument of `wrapper`, wherever it's nested,
+/// e.g. `Customer` of `ToOne<crate::entities::Customer>`
+pub fn get_type_arg_of(ty: &Type, wrapper: &str) -> Option<Ident> {
+    if let Type::Path(p) = ty {
+        for seg in &p.path.segments {
+            if let PathArguments::AngleBracketed(args) = &seg.arguments {
+                for arg in args.args.iter() {
+                    if let GenericArgument::Type(a) = arg {
+                        if seg.ident == wrapper {
+                            if let Type::Path(target) = a {
+                                return target.path.segments.last().map(|s| s.ident.clone());
+                            }
+                        }
+                        if let Some(ident) = get_type_arg_of(a, wrapper) {
+                            return Some(ident);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+#[test]
+fn type_arg_of_wrapper() {
+    for path in vec![
+        syn::parse_quote!(ToOne<Customer>),
+        syn::parse_quote!(relations::ToOne<crate::entities::Customer>),
+        syn::parse_quote!(Option<ToOne<entities::Customer>>),
+    ] {
+        let ident = get_type_arg_of(&path, "ToOne").map(|i| i.to_string());
+        assert_eq!(Some("Customer".to_string()), ident);
+    }
+    assert!(get_type_arg_of(&syn::parse_quote!(Vec<String>), "ToOne").is_none());
+}
+
 #[cfg(test)]
 #[test]
 fn recursively_get_idents() {
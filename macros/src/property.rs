@@ -3,7 +3,7 @@ use std::option::Option;
 use objectbox_generator::id;
 use objectbox_generator::ob_consts as consts;
 
-use crate::path_visitor::get_idents_from_path;
+use crate::path_visitor::{get_idents_from_path, get_type_arg_of};
 use crate::IdUidMacroHelper;
 
 // TODO implement flags, reference: https://github.com/objectbox/objectbox-dart/blob/main/generator/lib/src/entity_resolver.dart#L23-L30
@@ -15,6 +15,13 @@ pub struct Property {
     pub id: id::IdUid,
     pub flags: consts::OBXPropertyFlags,
     pub index_id: Option<String>,
+    pub relation_target: Option<String>,
+    /// `Option<T>`, None is stored as an absent value, i.e. null
+    pub optional: bool,
+    /// The field type of Date and DateNano properties, e.g. `DateTime<Utc>`
+    pub time_type: Option<String>,
+    /// The path of the PropertyConverter of the field
+    pub converter: Option<String>,
 }
 
 impl Property {
@@ -25,29 +32,60 @@ impl Property {
             id: id::IdUid::zero(),
             flags: 0,
             index_id: None,
+            relation_target: None,
+            optional: false,
+            time_type: None,
+            converter: None,
         }
     }
 
+    /// Only the given params are applied, flags are added to the existing ones
     pub(crate) fn scan_obx_property_type_and_flags(
         mnv: &syn::MetaNameValue,
-    ) -> (consts::OBXPropertyType, consts::OBXPropertyFlags) {
-        let mut obx_property_type: consts::OBXPropertyType = 0;
-        let mut obx_property_flags: consts::OBXPropertyFlags = 0;
-
-        if let syn::Lit::Int(li) = &mnv.lit {
-            let result = li.base10_parse::<consts::OBXPropertyFlags>();
-            if let Ok(value) = result {
-                if let Some(ident) = mnv.path.get_ident() {
-                    let param_name: &str = &ident.to_string();
-                    match param_name {
-                        "type" => obx_property_type = value,
-                        "flags" => obx_property_flags = value,
+        obx_property_type: &mut consts::OBXPropertyType,
+        obx_property_flags: &mut consts::OBXPropertyFlags,
+        converter: &mut Option<String>,
+    ) {
+        let param_name = match mnv.path.get_ident() {
+            Some(ident) => ident.to_string(),
+            None => return,
+        };
+
+        match &mnv.lit {
+            syn::Lit::Int(li) => {
+                if let Ok(value) = li.base10_parse::<consts::OBXPropertyFlags>() {
+                    match param_name.as_str() {
+                        "type" => *obx_property_type = value,
+                        "flags" => *obx_property_flags |= value,
                         _ => {}
                     }
                 }
             }
+            syn::Lit::Str(ls) if param_name == "type" => match ls.value().as_str() {
+                "date" => *obx_property_type = consts::OBXPropertyType_Date,
+                "date_nano" => *obx_property_type = consts::OBXPropertyType_DateNano,
+                // the storage of fields with a converter
+                "long" => *obx_property_type = consts::OBXPropertyType_Long,
+                "string" => *obx_property_type = consts::OBXPropertyType_String,
+                "bytes" => *obx_property_type = consts::OBXPropertyType_ByteVector,
+                other => panic!(
+                    "Unsupported property type: '{}', expected 'date', 'date_nano', 'long', 'string' or 'bytes'",
+                    other
+                ),
+            },
+            syn::Lit::Str(ls) if param_name == "convert" => *converter = Some(ls.value()),
+            syn::Lit::Str(ls) if param_name == "on_conflict" => match ls.value().as_str() {
+                "fail" => {}
+                "replace" => {
+                    *obx_property_flags |= consts::OBXPropertyFlags_UNIQUE_ON_CONFLICT_REPLACE
+                }
+                other => panic!(
+                    "Unsupported on_conflict strategy: '{}', expected 'fail' or 'replace'",
+                    other
+                ),
+            },
+            _ => {}
         }
-        (obx_property_type, obx_property_flags)
     }
 
     pub(crate) fn from_syn_field(field: &syn::Field) -> Option<Property> {
@@ -59,6 +97,10 @@ impl Property {
             id,
             flags: obx_property_flags,
             index_id,
+            relation_target,
+            optional,
+            time_type,
+            converter,
         } = &mut property;
 
         if let Some(ident) = &field.ident {
@@ -95,7 +137,6 @@ impl Property {
                             *index_id = Some("0:0".to_owned());
                         } // id, uid, type
                         "backlink" => {}
-                        // "transient" => { quote::__private::ext::RepToTokensExt::next(&a); }, // TODO test if this really skips
                         "property" => {} // id, uid, type, flags
                         _ => {
                             // skip if not ours
@@ -106,14 +147,18 @@ impl Property {
 
                 // TODO move out as generalized function with lambda
                 // that parses depending on given attrib parameter names
-                // given by 'index', 'backlink', 'transient', 'property'
+                // given by 'index', 'backlink', 'property'
                 if let syn::parse::Result::Ok(m) = a.parse_meta() {
                     match m {
                         // single parameter
                         syn::Meta::NameValue(mnv) => {
                             id.update_from_scan(&mnv);
-                            (*obx_property_type, *obx_property_flags) =
-                                Self::scan_obx_property_type_and_flags(&mnv);
+                            Self::scan_obx_property_type_and_flags(
+                                &mnv,
+                                obx_property_type,
+                                obx_property_flags,
+                                converter,
+                            );
                         }
                         // multiple parameters
                         syn::Meta::List(meta_list) => {
@@ -121,8 +166,12 @@ impl Property {
                                 if let syn::NestedMeta::Meta(meta) = nm {
                                     if let syn::Meta::NameValue(mnv) = meta {
                                         id.update_from_scan(&mnv);
-                                        (*obx_property_type, *obx_property_flags) =
-                                            Self::scan_obx_property_type_and_flags(&mnv);
+                                        Self::scan_obx_property_type_and_flags(
+                                            &mnv,
+                                            obx_property_type,
+                                            obx_property_flags,
+                                            converter,
+                                        );
                                     }
                                 }
                             });
@@ -132,15 +181,62 @@ impl Property {
                 }
             }
 
-            let idents = get_idents_from_path(&field.ty);
+            // Option<T> is mapped like T, but nullable
+            let ty = match option_inner_type(&field.ty) {
+                Some(inner) => {
+                    *optional = true;
+                    inner
+                }
+                None => &field.ty,
+            };
+            let idents = get_idents_from_path(ty);
+
+            // stored as a long by default, the generated code checks the Stored type
+            if let Some(conv) = converter {
+                match *obx_property_type {
+                    0 => *obx_property_type = consts::OBXPropertyType_Long,
+                    consts::OBXPropertyType_Long
+                    | consts::OBXPropertyType_String
+                    | consts::OBXPropertyType_ByteVector => {}
+                    _ => panic!(
+                        "The field {} with the converter {} is stored as type 'long', 'string' or 'bytes'",
+                        new_name, conv
+                    ),
+                }
+                return Some(property);
+            }
+
+            // ToOne<Target>, stored as the id of the target, with an implicit index
+            if let Some(target) = get_type_arg_of(&field.ty, "ToOne") {
+                if *optional {
+                    panic!(
+                        "Option<ToOne<{}>> is not supported, use ToOne<{}>, which has no target by default: {}",
+                        target, target, new_name
+                    );
+                }
+                *obx_property_type = consts::OBXPropertyType_Relation;
+                *obx_property_flags |= consts::OBXPropertyFlags_INDEXED
+                    | consts::OBXPropertyFlags_INDEX_PARTIAL_SKIP_ZERO;
+                *index_id = Some("0:0".to_owned());
+                *relation_target = Some(target.to_string());
+                return Some(property);
+            }
+
+            // stored as milliseconds (Date), or nanoseconds (DateNano) since the epoch
+            if idents
+                .iter()
+                .any(|i| i == "DateTime" || i == "NaiveDateTime" || i == "SystemTime")
+            {
+                if *obx_property_type != consts::OBXPropertyType_DateNano {
+                    *obx_property_type = consts::OBXPropertyType_Date;
+                }
+                *time_type = Some(quote::ToTokens::to_token_stream(ty).to_string());
+                return Some(property);
+            }
+
             let ident_joined = idents.iter().map(|i| i.to_string()).collect::<String>();
             let ident = ident_joined.as_str();
 
-            // TODO discuss support for Option<Primitive>,
-            // index is a special case, where index == 0, has a special meaning
-            // wrt to the Store and Box (not rust's)
-            // e.g. the indent.as_str() would be for Option<String> => OptionString
-
             *obx_property_type = match ident {
                 "bool" => consts::OBXPropertyType_Bool,
                 "i8" => consts::OBXPropertyType_Byte,
@@ -173,3 +269,35 @@ impl Property {
         None
     }
 }
+
+// T of Option<T>, also if Option is given by its path, e.g. std::option::Option<T>
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(tp) = ty {
+        if let Some(segment) = tp.path.segments.last() {
+            if segment.ident != "Option" {
+                return None;
+            }
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                    return Some(inner);
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+#[test]
+fn option_inner_type_of_paths() {
+    for ty in vec![
+        syn::parse_quote!(Option<String>),
+        syn::parse_quote!(std::option::Option<String>),
+        syn::parse_quote!(core::option::Option<String>),
+    ] {
+        let inner = option_inner_type(&ty).map(|t| quote::ToTokens::to_token_stream(t).to_string());
+        assert_eq!(Some("String".to_string()), inner);
+    }
+    assert!(option_inner_type(&syn::parse_quote!(Vec<String>)).is_none());
+    assert!(option_inner_type(&syn::parse_quote!(option::Vec<String>)).is_none());
+}
@@ -1,9 +1,14 @@
 #![allow(dead_code)]
-use crate::{c, error};
+use std::marker::PhantomData;
+
+use crate::c::{self, *};
+use crate::error;
+use crate::traits::OBBlanket;
+use flatbuffers::FlatBufferBuilder;
 
-// TODO required for putAsync and putQueued
 pub(crate) struct Async {
     pub(crate) obx_async: *mut c::OBX_async,
+    // the shared instance of a box is owned by the box, never close it
     ptr_closed: bool,
 }
 
@@ -19,17 +24,16 @@ impl Drop for Async {
 }
 
 impl Async {
-    // TODO test
+    /// The shared instance of the box, with the default enqueue timeout
     pub fn from_box(obx_box: *mut c::OBX_box) -> error::Result<Self> {
         unsafe {
             c::new_mut(c::obx_async(obx_box)).map(|ptr| Async {
                 obx_async: ptr,
-                ptr_closed: false,
+                ptr_closed: true,
             })
         }
     }
 
-    // TODO test
     pub(crate) fn remove_with_id(&mut self, id: c::obx_id) -> error::Result<bool> {
         unsafe {
             let code = c::obx_async_remove(self.obx_async, id);
@@ -37,7 +41,7 @@ impl Async {
         }
     }
 
-    // TODO test
+    /// A new instance, that is closed on drop
     pub(crate) fn from_box_with_timeout(
         obx_box: *mut c::OBX_box,
         enqueue_timeout_millis: u64,
@@ -50,44 +54,83 @@ impl Async {
         }
     }
 
-    // TODO test
     pub(crate) fn close(&mut self) -> error::Result<()> {
         c::call(unsafe { c::obx_async_close(self.obx_async) })
     }
 
-    // TODO finish
-    /*
-    pub(crate) fn put5(&mut self, id: c::obx_id, data: ConstVoidPtr, size: usize, mode: c::OBXPutMode) -> error::Result<c::obx_id> {
-      // TODO depending on the state of the object determine the mode (PUT, INSERT, UPDATE)
-      // TODO if this is a fresh new object, get an id
-      c::call(unsafe { c::obx_async_put5(self.obx_async, id, data, size, mode) }).err();
-      // TODO return this error::Result<id>
+    /// Enqueues the data, the id must not be zero
+    pub(crate) fn put5(
+        &mut self,
+        id: c::obx_id,
+        data: &[u8],
+        mode: c::OBXPutMode,
+    ) -> error::Result<()> {
+        c::call(unsafe {
+            c::obx_async_put5(self.obx_async, id, data.as_ptr().cast(), data.len(), mode)
+        })
     }
-    */
+}
 
-    /*
-    // fn put(&self, async_: *mut OBX_async, id: obx_id, data: *const ::std::os::raw::c_void, size: usize) -> obx_err {
-    //     unsafe { obx_async_put(async_, id, data, size) }
-    // }
+/// Queues writes of T, that are executed in the background, in batches.
+/// The calls return as soon as the operation is enqueued;
+/// Store::await_async_completion waits for them to be written.
+/// Pending changes of standalone relations are not applied.
+pub struct AsyncBox<'a, T: OBBlanket> {
+    async_: Async,
+    obx_box: *mut OBX_box,
+    builder: FlatBufferBuilder<'a>,
+    phantom_data: PhantomData<T>,
+}
 
-    // fn insert(&self, async_: *mut OBX_async, id: obx_id, data: *const ::std::os::raw::c_void, size: usize) -> obx_err {
-    //     unsafe { obx_async_insert(async_, id, data, size) }
-    // }
+impl<T: OBBlanket> AsyncBox<'_, T> {
+    pub(crate) fn new(obx_box: *mut OBX_box, async_: Async) -> Self {
+        AsyncBox {
+            async_,
+            obx_box,
+            builder: FlatBufferBuilder::new(),
+            phantom_data: PhantomData,
+        }
+    }
 
-    // fn update(&self, async_: *mut OBX_async, id: obx_id, data: *const ::std::os::raw::c_void, size: usize) -> obx_err {
-    //     unsafe { obx_async_update(async_, id, data, size) }
-    // }
+    // new objects get their id before they are enqueued, and keep the old one if that fails
+    fn enqueue(&mut self, object: &mut T, mode: OBXPutMode) -> error::Result<c::obx_id> {
+        let old_id = object.get_id();
+        let id = unsafe { obx_box_id_for_put(self.obx_box, old_id) };
+        if id == 0 {
+            return error::Error::new_local("Error: unable to get an id for the object")
+                .as_result();
+        }
+        object.set_id(id);
+        object.flatten(&mut self.builder);
+        let data = Vec::from(self.builder.finished_data());
+        if let Err(err) = self.async_.put5(id, &data, mode) {
+            object.set_id(old_id);
+            return Err(err);
+        }
+        Ok(id)
+    }
 
-    // fn put_object(&self, async_: *mut OBX_async, data: *mut ::std::os::raw::c_void, size: usize) -> obx_id {
-    //     unsafe { obx_async_put_object(async_, data, size) }
-    // }
+    /// Inserts or updates the object, returns its (new) id
+    pub fn put(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+        self.enqueue(object, OBXPutMode_PUT)
+    }
 
-    // fn put_object4(&self, async_: *mut OBX_async, data: *mut ::std::os::raw::c_void, size: usize, mode: OBXPutMode) -> obx_id {
-    //     unsafe { obx_async_put_object4(async_, data, size, mode) }
-    // }
+    /// Fails in the background, if an object with the same id exists
+    pub fn insert(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+        self.enqueue(object, OBXPutMode_INSERT)
+    }
 
-    // fn insert_object(&self, async_: *mut OBX_async, data: *mut ::std::os::raw::c_void, size: usize) -> obx_id {
-    //     unsafe { obx_async_insert_object(async_, data, size) }
-    // }
-    */
+    /// Fails right away for new objects,
+    /// and in the background, if the object doesn't exist
+    pub fn update(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+        if object.get_id() == 0 {
+            return error::Error::new_local("Error: only stored objects can be updated")
+                .as_result();
+        }
+        self.enqueue(object, OBXPutMode_UPDATE)
+    }
+
+    pub fn remove(&mut self, id: c::obx_id) -> error::Result<()> {
+        self.async_.remove_with_id(id).map(|_| ())
+    }
 }
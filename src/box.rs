@@ -1,28 +1,29 @@
 #![allow(dead_code)]
-use std::ptr;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::c::{self, *};
 use crate::error;
+use crate::r#async::{Async, AsyncBox};
 
-use crate::cursor::Cursor;
+use crate::cursor::{ref_from_data, Cursor};
+use crate::iter::Iter;
 use crate::query::builder::Builder;
 use crate::query::condition::Condition;
+use crate::query::link::Link;
 use crate::query::Query;
-use crate::traits::{EntityFactoryExt, OBBlanket};
-use crate::util::{MutConstVoidPtr, NOT_FOUND_404};
+use crate::traits::{EntityFactoryExt, OBBlanket, RefExt, UniqueExt};
+use crate::txn::TxScope;
 use flatbuffers::FlatBufferBuilder;
 
 // This Box type will confuse a lot of rust users of std::boxed::Box
 pub struct Box<'a, T: OBBlanket> {
-    pub(crate) helper: Rc<dyn EntityFactoryExt<T>>,
+    pub(crate) helper: Arc<dyn EntityFactoryExt<T>>,
     pub(crate) obx_box: *mut OBX_box,
     builder: FlatBufferBuilder<'a>,
-    // pub(crate) async_: std::boxed::Box<Async>, // TODO
 }
 
 impl<T: OBBlanket> Box<'_, T> {
-    pub(crate) fn new(store: *mut OBX_store, helper: Rc<dyn EntityFactoryExt<T>>) -> Self {
+    pub(crate) fn new(store: *mut OBX_store, helper: Arc<dyn EntityFactoryExt<T>>) -> Self {
         unsafe {
             let obx_box = c::obx_box(store, helper.get_entity_id());
 
@@ -34,6 +35,20 @@ impl<T: OBBlanket> Box<'_, T> {
         }
     }
 
+    /// Queued writes, with the enqueue timeout of the store options
+    pub fn async_ops(&self) -> error::Result<AsyncBox<'_, T>> {
+        Async::from_box(self.obx_box).map(|a| AsyncBox::new(self.obx_box, a))
+    }
+
+    /// Queued writes, that wait up to the given time for space in a full queue
+    pub fn async_ops_with_timeout(
+        &self,
+        enqueue_timeout_millis: u64,
+    ) -> error::Result<AsyncBox<'_, T>> {
+        Async::from_box_with_timeout(self.obx_box, enqueue_timeout_millis)
+            .map(|a| AsyncBox::new(self.obx_box, a))
+    }
+
     // This should only be exposed between threads, channels, etc.
     pub(crate) fn get_store(&self) -> *mut OBX_store {
         unsafe { obx_box_store(self.obx_box) }
@@ -216,23 +231,56 @@ impl<T: OBBlanket> Box<'_, T> {
             c::get_result(obx_box_count(self.obx_box, limit, out_count), *out_count)
         }
     }
-    /*
-      pub fn get_backlink_ids(&self, property_id: obx_schema_id, id: obx_id) -> *mut OBX_id_array {
-          unsafe { obx_box_get_backlink_ids(self.obx_box, property_id, id) }
-      }
+    /// Adds the target to the standalone relation of the source object
+    pub fn rel_put(
+        &self,
+        relation_id: obx_schema_id,
+        source_id: obx_id,
+        target_id: obx_id,
+    ) -> error::Result<()> {
+        c::call(unsafe { obx_box_rel_put(self.obx_box, relation_id, source_id, target_id) })
+    }
 
-      pub fn rel_put(&self, relation_id: obx_schema_id, source_id: obx_id, target_id: obx_id) -> obx_err {
-          unsafe { obx_box_rel_put(self.obx_box, relation_id, source_id, target_id) }
-      }
+    /// Removes the target from the standalone relation of the source object
+    pub fn rel_remove(
+        &self,
+        relation_id: obx_schema_id,
+        source_id: obx_id,
+        target_id: obx_id,
+    ) -> error::Result<()> {
+        c::call(unsafe { obx_box_rel_remove(self.obx_box, relation_id, source_id, target_id) })
+    }
 
-      pub fn rel_remove(&self, relation_id: obx_schema_id, source_id: obx_id, target_id: obx_id) -> obx_err {
-          unsafe { obx_box_rel_remove(self.obx_box, relation_id, source_id, target_id) }
-      }
+    /// Target ids of the standalone relation of the source object
+    pub fn rel_get_ids(
+        &self,
+        relation_id: obx_schema_id,
+        id: obx_id,
+    ) -> error::Result<Vec<obx_id>> {
+        c::get_ids_from_array(unsafe { obx_box_rel_get_ids(self.obx_box, relation_id, id) })
+    }
 
-      pub fn rel_get_ids(&self, relation_id: obx_schema_id, id: obx_id) -> *mut OBX_id_array {
-          unsafe { obx_box_rel_get_ids(self.obx_box, relation_id, id) }
-      }
+    /// Ids of the objects in this Box, whose relation property points to the target id
+    pub fn get_backlink_ids(
+        &self,
+        property_id: obx_schema_id,
+        id: obx_id,
+    ) -> error::Result<Vec<obx_id>> {
+        c::get_ids_from_array(unsafe { obx_box_get_backlink_ids(self.obx_box, property_id, id) })
+    }
 
+    /// Ids of the objects in this Box, whose standalone relation contains the target id
+    pub fn rel_get_backlink_ids(
+        &self,
+        relation_id: obx_schema_id,
+        id: obx_id,
+    ) -> error::Result<Vec<obx_id>> {
+        c::get_ids_from_array(unsafe {
+            obx_box_rel_get_backlink_ids(self.obx_box, relation_id, id)
+        })
+    }
+
+    /*
       // TODO convert user_data to Vec<u8>
       pub fn visit_all(&mut self, visitor: obx_data_visitor, user_data: *mut ::std::os::raw::c_void) -> obx_err {
         unsafe {
@@ -247,12 +295,6 @@ impl<T: OBBlanket> Box<'_, T> {
             }
         }
 
-      pub fn rel_get_backlink_ids(&mut self, relation_id: obx_schema_id, id: obx_id) -> *mut OBX_id_array {
-        unsafe {
-            obx_box_rel_get_backlink_ids(self.obx_box, relation_id, id)
-        }
-      }
-
       pub fn ts_min_max(&mut self, out_min_id: *mut obx_id, out_min_value: *mut i64, out_max_id: *mut obx_id, out_max_value: *mut i64) -> obx_err {
         unsafe {
             obx_box_ts_min_max(self.obx_box, out_min_id, out_min_value, out_max_id, out_max_value)
@@ -277,42 +319,129 @@ impl<T: OBBlanket> Box<'_, T> {
         cursor: &mut Cursor<T>,
         object: &mut T,
     ) -> error::Result<c::obx_id> {
+        cursor.put_entity(&mut self.builder, object)
+    }
+
+    pub fn put(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+        let mut cursor = Cursor::new(true, self.get_store(), self.helper.clone())?;
+
+        let new_id = self.put_entity_in_ob(&mut cursor, object)?;
+        cursor.commit()?;
+
+        Ok(new_id)
+    }
+
+    /// Like put, but a clash on a unique property is reported
+    /// with the property and the id of the existing object, see Error::unique_violation.
+    /// A new object keeps its id of zero on failure.
+    /// Optional (`Option<_>`) unique properties aren't looked up, a clash
+    /// on one of them is returned as the plain native error.
+    pub fn put_checked(&mut self, object: &mut T) -> error::Result<c::obx_id>
+    where
+        T: UniqueExt,
+    {
         let old_id = object.get_id();
-        let is_object_new = old_id == 0;
-        let new_id = cursor.id_for_put(old_id);
-        object.set_id(new_id);
+        let err = match self.put(object) {
+            Ok(id) => return Ok(id),
+            Err(err) => err,
+        };
+        if err.kind() == error::ErrorKind::UniqueViolated {
+            for (property, mut condition) in object.unique_conditions() {
+                let ids = self.query(&mut condition)?.find_ids()?;
+                if let Some(existing_id) = ids.into_iter().find(|id| *id != old_id) {
+                    return error::Error::new_unique_violation(property, existing_id).as_result();
+                }
+            }
+        }
+        Err(err)
+    }
 
-        object.flatten(&mut self.builder);
-        let data = Vec::from(self.builder.finished_data());
+    pub fn put_many(&mut self, objects: Vec<&mut T>) -> error::Result<Vec<c::obx_id>> {
+        let mut cursor = Cursor::new(true, self.get_store(), self.helper.clone())?;
+
+        let mut vec_out = Vec::<c::obx_id>::new();
 
-        if is_object_new {
-            cursor.put_new(new_id, &data)?;
-        } else {
-            cursor.put(new_id, &data)?;
+        for o in objects {
+            vec_out.push(self.put_entity_in_ob(&mut cursor, o)?);
         }
 
-        Ok(new_id)
+        cursor.commit()?;
+        Ok(vec_out)
     }
 
-    pub fn put(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+    /// Puts the object with the given OBXPutMode_* semantics, returns its (new) id
+    pub fn put_with_mode(&mut self, object: &mut T, mode: OBXPutMode) -> error::Result<c::obx_id> {
         let mut cursor = Cursor::new(true, self.get_store(), self.helper.clone())?;
 
-        let new_id = self.put_entity_in_ob(&mut cursor, object);
-        cursor.get_tx().success()?;
+        let new_id = cursor.put_entity_with_mode(&mut self.builder, object, mode)?;
+        cursor.commit()?;
 
-        new_id
+        Ok(new_id)
     }
 
-    pub fn put_many(&mut self, objects: Vec<&mut T>) -> error::Result<Vec<c::obx_id>> {
+    /// Fails with ErrorKind::IdAlreadyExists, if an object with the same id exists
+    pub fn insert(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+        self.put_with_mode(object, OBXPutMode_INSERT)
+    }
+
+    /// Fails with ErrorKind::IdNotFound, if the object doesn't exist
+    pub fn update(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+        self.put_with_mode(object, OBXPutMode_UPDATE)
+    }
+
+    /// Puts the objects in one transaction, with the given OBXPutMode_* semantics.
+    /// If fail_on_id_failure is true, an object that exists (insert), or doesn't (update),
+    /// fails the whole batch, otherwise only that object is skipped and its id is 0.
+    pub fn put_many_with_mode(
+        &mut self,
+        objects: Vec<&mut T>,
+        mode: OBXPutMode,
+        fail_on_id_failure: bool,
+    ) -> error::Result<Vec<c::obx_id>> {
+        let mut objects = objects;
+        let old_ids: Vec<c::obx_id> = objects.iter().map(|o| o.get_id()).collect();
+        let result = self.put_each_with_mode(&mut objects, mode, fail_on_id_failure);
+        if result.is_err() {
+            // nothing was stored, the objects get their previous ids back
+            for (o, old_id) in objects.iter_mut().zip(old_ids) {
+                o.set_id(old_id);
+            }
+        }
+        result
+    }
+
+    // a single transaction, rolled back on failure
+    fn put_each_with_mode(
+        &mut self,
+        objects: &mut [&mut T],
+        mode: OBXPutMode,
+        fail_on_id_failure: bool,
+    ) -> error::Result<Vec<c::obx_id>> {
         let mut cursor = Cursor::new(true, self.get_store(), self.helper.clone())?;
 
         let mut vec_out = Vec::<c::obx_id>::new();
 
-        for o in objects {
-            vec_out.push(self.put_entity_in_ob(&mut cursor, o)?);
+        for o in objects.iter_mut() {
+            if !fail_on_id_failure && mode == OBXPutMode_UPDATE && o.get_id() == 0 {
+                vec_out.push(0);
+                continue;
+            }
+            match cursor.put_entity_with_mode(&mut self.builder, o, mode) {
+                Ok(id) => vec_out.push(id),
+                Err(err)
+                    if !fail_on_id_failure
+                        && matches!(
+                            err.kind(),
+                            error::ErrorKind::IdAlreadyExists | error::ErrorKind::IdNotFound
+                        ) =>
+                {
+                    vec_out.push(0)
+                }
+                Err(err) => return Err(err),
+            }
         }
 
-        cursor.get_tx().success()?;
+        cursor.commit()?;
         Ok(vec_out)
     }
 
@@ -327,6 +456,28 @@ impl<T: OBBlanket> Box<'_, T> {
         cursor.get_entity(id)
     }
 
+    /// A view of the object, that reads its fields straight from the stored data,
+    /// without copying them. It lives as long as the read transaction.
+    pub fn get_ref<'tx>(
+        &self,
+        tx: &'tx TxScope,
+        id: c::obx_id,
+    ) -> error::Result<Option<T::Ref<'tx>>>
+    where
+        T: RefExt,
+    {
+        // a write could move the data, also one the read is nested in
+        if tx.is_write() || tx.is_nested_in_write() {
+            return error::Error::new_local("Error: views need a read transaction").as_result();
+        }
+        let mut cursor = Cursor::new_in_tx(&tx.tx, self.helper.clone())?;
+        unsafe {
+            cursor
+                .get_data(id)
+                .map(|data| data.map(|data| ref_from_data::<T>(data)))
+        }
+    }
+
     pub fn get_many(&self, ids: &[c::obx_id]) -> error::Result<Vec<Option<T>>> {
         let mut cursor = Cursor::new(false, self.get_store(), self.helper.clone())?;
 
@@ -341,25 +492,13 @@ impl<T: OBBlanket> Box<'_, T> {
     /// Returns all stored objects in this Box
     pub fn get_all(&self) -> error::Result<Vec<T>> {
         let mut cursor = Cursor::new(false, self.get_store(), self.helper.clone())?;
+        cursor.get_entities()
+    }
 
-        let data_ptr_ptr: *mut *mut u8 = &mut ptr::null_mut();
-
-        let size_ptr: *mut usize = &mut 0;
-
-        let mut vec: Vec<T> = Vec::new();
-
-        let mut code = cursor.first(data_ptr_ptr as MutConstVoidPtr, size_ptr)?;
-
-        // c::OBX_NOT_FOUND was a C #define that became a u32
-        // which is incompatible with obx_err === i32
-        while code != NOT_FOUND_404 {
-            unsafe {
-                vec.push(cursor.from_raw_parts_to_object(data_ptr_ptr, size_ptr));
-            }
-            code = cursor.next(data_ptr_ptr as MutConstVoidPtr, size_ptr)?;
-        }
-
-        Ok(vec)
+    /// Iterates over all stored objects in this Box, in id order,
+    /// reading one object at a time in a read transaction held by the iterator
+    pub fn iter(&self) -> error::Result<Iter<'_, T>> {
+        Cursor::new(false, self.get_store(), self.helper.clone()).map(Iter::walk)
     }
 
     // TODO
@@ -377,4 +516,15 @@ impl<T: OBBlanket> Box<'_, T> {
     pub fn query(&self, root: &mut Condition<T>) -> error::Result<Query<T>> {
         self.query_builder(root)?.build()
     }
+
+    /// Immediately build a query, that only has conditions on the objects related through link
+    pub fn query_link<Target: OBBlanket>(
+        &self,
+        link: &Link<T, Target>,
+        condition: &mut Condition<Target>,
+    ) -> error::Result<Query<T>> {
+        Builder::<T>::new_empty(self)?
+            .link(link, condition)?
+            .build()
+    }
 }
@@ -8,7 +8,7 @@
 #![allow(unused_assignments)]
 include!("./c_bindings.rs");
 
-use crate::error::Error;
+use crate::error::{Error, ErrorKind};
 use std::{error, ffi, fmt, ptr};
 
 /**
@@ -20,7 +20,7 @@ use std::{error, ffi, fmt, ptr};
 
 pub enum NativeErrorKind {
     NullPtr,
-    Other, // TODO maybe implement types based on OBX_ERROR_*
+    Other, // mapped to an ErrorKind by its OBX_ERROR_* code
 }
 
 #[derive(Debug, Clone)]
@@ -31,6 +31,22 @@ pub struct NativeError {
 }
 
 impl NativeError {
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    pub fn secondary(&self) -> i32 {
+        self.secondary
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::from_code(self.code)
+    }
+
     fn _new(_kind: NativeErrorKind) -> NativeError {
         unsafe {
             let mut c_code: i32 = 0;
@@ -118,6 +134,20 @@ pub fn get_result_from_ptr<S, T>(ptr: *mut S, returnValue: T) -> Result<T, Error
     }
 }
 
+/// Validates the id array returned from a native call, copies and frees it.
+pub(crate) fn get_ids_from_array(ptr: *mut OBX_id_array) -> Result<Vec<obx_id>, Error> {
+    let array = get_result_from_ptr(ptr, ptr)?;
+    unsafe {
+        let ids = if (*array).count == 0 {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts((*array).ids, (*array).count).to_vec()
+        };
+        obx_id_array_free(array);
+        Ok(ids)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
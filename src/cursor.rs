@@ -1,20 +1,39 @@
 #![allow(dead_code)]
-use std::{ptr, rc::Rc, slice::from_raw_parts};
+use std::{ptr, slice::from_raw_parts, sync::Arc};
 
 use crate::{
     c::{self, *},
     error,
-    traits::EntityFactoryExt,
-    txn::Tx,
+    traits::{EntityFactoryExt, OBBlanket, RefExt},
+    txn::{Tx, TxOutcome},
     util::{MutConstVoidPtr, ToCVoid, NOT_FOUND_404},
 };
+use flatbuffers::FlatBufferBuilder;
 
 // The best article ever on ffi
 // https://blog.guillaume-gomez.fr/articles/2021-07-29+Interacting+with+data+from+FFI+in+Rust
 pub(crate) struct Cursor<T> {
-    helper: Rc<dyn EntityFactoryExt<T>>,
+    helper: Arc<dyn EntityFactoryExt<T>>,
     pub(crate) obx_cursor: *mut c::OBX_cursor,
-    tx: Tx,
+    // None when the cursor runs inside a transaction owned by someone else
+    tx: Option<Tx>,
+    // the pending relation changes applied by put are kept until it's committed
+    outcome: TxOutcome,
+}
+
+/// Inflates an object from its flatbuffer data, which isn't verified
+pub(crate) unsafe fn object_from_data<T>(helper: &dyn EntityFactoryExt<T>, data: &[u8]) -> T {
+    let first_offset: usize = data[0].into();
+
+    // TODO check speed improvement if table is recycled
+    let mut table = flatbuffers::Table::new(data, first_offset);
+    helper.make(&mut table)
+}
+
+/// A view of the object in its flatbuffer data, which isn't verified
+pub(crate) unsafe fn ref_from_data<T: RefExt>(data: &[u8]) -> T::Ref<'_> {
+    let first_offset: usize = data[0].into();
+    T::make_ref(flatbuffers::Table::new(data, first_offset))
 }
 
 impl<T> Drop for Cursor<T> {
@@ -34,7 +53,7 @@ impl<T> Cursor<T> {
     pub(crate) fn new(
         is_mut: bool,
         store: *mut c::OBX_store,
-        helper: Rc<dyn EntityFactoryExt<T>>,
+        helper: Arc<dyn EntityFactoryExt<T>>,
     ) -> error::Result<Self> {
         let entity_id = helper.get_entity_id();
         let tx = if is_mut {
@@ -42,15 +61,33 @@ impl<T> Cursor<T> {
         } else {
             Tx::new(store)
         }?;
+        let outcome = tx.outcome.clone();
         c::new_mut(unsafe { c::obx_cursor(tx.obx_txn, entity_id) }).map(|obx_cursor| Cursor {
             helper,
             obx_cursor,
-            tx,
+            tx: Some(tx),
+            outcome,
         })
     }
 
-    pub(crate) fn get_tx(&mut self) -> &mut Tx {
-        &mut self.tx
+    /// The cursor borrows the transaction, the owner of `tx`
+    /// decides whether it's committed or not.
+    pub(crate) fn new_in_tx(tx: &Tx, helper: Arc<dyn EntityFactoryExt<T>>) -> error::Result<Self> {
+        let entity_id = helper.get_entity_id();
+        c::new_mut(unsafe { c::obx_cursor(tx.obx_txn, entity_id) }).map(|obx_cursor| Cursor {
+            helper,
+            obx_cursor,
+            tx: None,
+            outcome: tx.outcome.clone(),
+        })
+    }
+
+    /// Commits the owned write transaction, a borrowed one is left alone.
+    pub(crate) fn commit(&mut self) -> error::Result<()> {
+        match &mut self.tx {
+            Some(tx) => tx.success(),
+            None => Ok(()),
+        }
     }
 
     pub(crate) unsafe fn from_raw_parts_to_object(
@@ -59,11 +96,7 @@ impl<T> Cursor<T> {
         size_ptr: *mut usize,
     ) -> T {
         let data_slice = from_raw_parts(*data_ptr_ptr, *size_ptr);
-        let first_offset: usize = data_slice[0].into();
-
-        // TODO check speed improvement if table is recycled
-        let mut table = flatbuffers::Table::new(data_slice, first_offset);
-        self.helper.make(&mut table)
+        object_from_data(self.helper.as_ref(), data_slice)
     }
 
     pub(crate) fn get_entity(&mut self, id: c::obx_id) -> error::Result<Option<T>> {
@@ -87,6 +120,66 @@ impl<T> Cursor<T> {
         }
     }
 
+    /// The stored data of the object, which is valid until the transaction ends,
+    /// or is written to, the caller picks a lifetime within those bounds.
+    pub(crate) unsafe fn get_data<'tx>(
+        &mut self,
+        id: c::obx_id,
+    ) -> error::Result<Option<&'tx [u8]>> {
+        let data_ptr_ptr: *mut *mut u8 = &mut ptr::null_mut();
+
+        let size_ptr: *mut usize = &mut 0;
+
+        self.get(id, data_ptr_ptr as MutConstVoidPtr, size_ptr)
+            .map(|code| {
+                if NOT_FOUND_404 == code {
+                    None
+                } else {
+                    Some(from_raw_parts(*data_ptr_ptr as *const u8, *size_ptr))
+                }
+            })
+    }
+
+    pub(crate) fn get_entities(&mut self) -> error::Result<Vec<T>> {
+        let data_ptr_ptr: *mut *mut u8 = &mut ptr::null_mut();
+
+        let size_ptr: *mut usize = &mut 0;
+
+        let mut vec: Vec<T> = Vec::new();
+
+        let mut code = self.first(data_ptr_ptr as MutConstVoidPtr, size_ptr)?;
+
+        // c::OBX_NOT_FOUND was a C #define that became a u32
+        // which is incompatible with obx_err === i32
+        while code != NOT_FOUND_404 {
+            unsafe {
+                vec.push(self.from_raw_parts_to_object(data_ptr_ptr, size_ptr));
+            }
+            code = self.next(data_ptr_ptr as MutConstVoidPtr, size_ptr)?;
+        }
+
+        Ok(vec)
+    }
+
+    /// The first or the next object, None after the last one
+    pub(crate) fn walk_entity(&mut self, first: bool) -> error::Result<Option<T>> {
+        let data_ptr_ptr: *mut *mut u8 = &mut ptr::null_mut();
+
+        let size_ptr: *mut usize = &mut 0;
+
+        let code = if first {
+            self.first(data_ptr_ptr as MutConstVoidPtr, size_ptr)?
+        } else {
+            self.next(data_ptr_ptr as MutConstVoidPtr, size_ptr)?
+        };
+
+        if code == NOT_FOUND_404 {
+            Ok(None)
+        } else {
+            unsafe { Ok(Some(self.from_raw_parts_to_object(data_ptr_ptr, size_ptr))) }
+        }
+    }
+
     pub(crate) fn id_for_put(&self, id_or_zero: obx_id) -> obx_id {
         unsafe { obx_cursor_id_for_put(self.obx_cursor, id_or_zero) }
     }
@@ -96,38 +189,30 @@ impl<T> Cursor<T> {
             c::obx_cursor_put(self.obx_cursor, id, data.to_const_c_void(), data.len())
         })
     }
-    /*
-      fn put4(
-          &mut self,
-          id: obx_id,
-          data: &Vec<u8>, // bad idea
-          mode: OBXPutMode,
-      ) {
-        c::call(unsafe { obx_cursor_put4(self.obx_cursor, id, data.to_const_c_void(), data.len(), mode) }).err();
-      }
-    */
+
+    pub(crate) fn put4(
+        &mut self,
+        id: obx_id,
+        data: &Vec<u8>,
+        mode: OBXPutMode,
+    ) -> error::Result<()> {
+        c::call(unsafe {
+            obx_cursor_put4(
+                self.obx_cursor,
+                id,
+                data.to_const_c_void(),
+                data.len(),
+                mode,
+            )
+        })
+    }
+
     pub(crate) fn put_new(&mut self, id: obx_id, data: &Vec<u8>) -> error::Result<()> {
         c::call(unsafe {
             obx_cursor_put_new(self.obx_cursor, id, data.to_const_c_void(), data.len())
         })
     }
     /*
-      fn insert(
-          &mut self,
-          id: obx_id,
-          data: &Vec<u8>, // bad idea
-      ) {
-          c::call(unsafe { obx_cursor_insert(self.obx_cursor, id, data.to_const_c_void(), data.len()) }).err()
-      }
-
-      fn update(
-          &mut self,
-          id: obx_id,
-          data: &Vec<u8>, // bad idea
-      ) {
-          c::call(unsafe { obx_cursor_update(self.obx_cursor, id, data.to_const_c_void(), data.len()) }).err()
-      }
-
       fn put_object(
           &self,
           data: *mut ::std::os::raw::c_void,
@@ -198,8 +283,11 @@ impl<T> Cursor<T> {
         }
     }
 
-    fn remove(&mut self, id: obx_id) -> error::Result<()> {
-        c::call(unsafe { obx_cursor_remove(self.obx_cursor, id) })
+    pub(crate) fn remove(&mut self, id: obx_id) -> error::Result<bool> {
+        unsafe {
+            let code = obx_cursor_remove(self.obx_cursor, id);
+            c::get_result(code, code == 0)
+        }
     }
 
     pub(crate) fn remove_all(&mut self) -> error::Result<()> {
@@ -227,7 +315,7 @@ impl<T> Cursor<T> {
 
     // TODO Determine: do we need a Tx for is_empty? Or just use the box
     // TODO test endianness
-    fn is_empty(&mut self) -> error::Result<bool> {
+    pub(crate) fn is_empty(&mut self) -> error::Result<bool> {
         unsafe {
             let out_is_empty: *mut bool = &mut false; // coerce
             c::call(obx_cursor_is_empty(
@@ -252,8 +340,10 @@ impl<T> Cursor<T> {
         entity_id: obx_schema_id,
         property_id: obx_schema_id,
         id: obx_id,
-    ) -> *mut OBX_id_array {
-        unsafe { obx_cursor_backlink_ids(self.obx_cursor, entity_id, property_id, id) }
+    ) -> error::Result<Vec<obx_id>> {
+        c::get_ids_from_array(unsafe {
+            obx_cursor_backlink_ids(self.obx_cursor, entity_id, property_id, id)
+        })
     }
 
     fn rel_put(
@@ -276,8 +366,10 @@ impl<T> Cursor<T> {
         })
     }
 
-    fn rel_ids(&self, relation_id: obx_schema_id, source_id: obx_id) -> *mut OBX_id_array {
-        unsafe { obx_cursor_rel_ids(self.obx_cursor, relation_id, source_id) }
+    fn rel_ids(&self, relation_id: obx_schema_id, source_id: obx_id) -> error::Result<Vec<obx_id>> {
+        c::get_ids_from_array(unsafe {
+            obx_cursor_rel_ids(self.obx_cursor, relation_id, source_id)
+        })
     }
 
     /*
@@ -300,3 +392,72 @@ impl<T> Cursor<T> {
     }
     */
 }
+
+impl<T: OBBlanket> Cursor<T> {
+    /// Assigns an id to new objects, then flattens and puts them,
+    /// the builder is passed in, so it can be recycled by the caller.
+    pub(crate) fn put_entity(
+        &mut self,
+        builder: &mut FlatBufferBuilder,
+        object: &mut T,
+    ) -> error::Result<c::obx_id> {
+        self.put_entity_with_mode(builder, object, OBXPutMode_PUT)
+    }
+
+    /// Like put_entity, with insert or update semantics, if given.
+    /// On failure, the object keeps its previous id.
+    pub(crate) fn put_entity_with_mode(
+        &mut self,
+        builder: &mut FlatBufferBuilder,
+        object: &mut T,
+        mode: OBXPutMode,
+    ) -> error::Result<c::obx_id> {
+        let old_id = object.get_id();
+        let is_object_new = old_id == 0;
+        if is_object_new && mode == OBXPutMode_UPDATE {
+            return error::Error::new_local("Error: only stored objects can be updated")
+                .as_result();
+        }
+        let new_id = self.id_for_put(old_id);
+        object.set_id(new_id);
+
+        object.flatten(builder);
+        let data = Vec::from(builder.finished_data());
+
+        let result = if is_object_new {
+            self.put_new(new_id, &data)
+        } else if mode == OBXPutMode_PUT {
+            self.put(new_id, &data)
+        } else {
+            self.put4(new_id, &data, mode)
+        };
+        if let Err(err) = result {
+            object.set_id(old_id);
+            return Err(err);
+        }
+
+        if let Err(err) = self.put_to_many_relations(object, new_id) {
+            object.set_id(old_id);
+            return Err(err);
+        }
+        object.attach_backlinks(new_id);
+
+        Ok(new_id)
+    }
+
+    // standalone relations, in the same transaction as the object
+    fn put_to_many_relations(&mut self, object: &mut T, id: c::obx_id) -> error::Result<()> {
+        let entity_id = self.helper.get_entity_id();
+        for (relation_id, to_many) in object.to_many_relations() {
+            let (added, removed) = to_many.pending();
+            for target_id in added {
+                self.rel_put(relation_id, id, target_id)?;
+            }
+            for target_id in removed {
+                self.rel_remove(relation_id, id, target_id)?;
+            }
+            to_many.applied(entity_id, relation_id, id, self.outcome.clone());
+        }
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use std::{error, fmt, result};
 enum Repr {
     Native(c::NativeError),
     Local(String),
+    UniqueViolation(UniqueViolation),
 }
 
 #[derive(Clone)]
@@ -13,6 +14,140 @@ pub struct Error {
     repr: Repr,
 }
 
+/// The kind of an error, native errors by their OBX_ERROR_* code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    IllegalState,
+    IllegalArgument,
+    Allocation,
+    NumericOverflow,
+    FeatureNotAvailable,
+    ShuttingDown,
+    NoErrorInfo,
+    General,
+    Unknown,
+    DbFull,
+    MaxReadersExceeded,
+    StoreMustShutdown,
+    MaxDataSizeExceeded,
+    DbGeneral,
+    StorageGeneral,
+    UniqueViolated,
+    NonUniqueResult,
+    PropertyTypeMismatch,
+    IdAlreadyExists,
+    IdNotFound,
+    TimeSeries,
+    ConstraintViolated,
+    StdIllegalArgument,
+    StdOutOfRange,
+    StdLength,
+    StdBadAlloc,
+    StdRange,
+    StdOverflow,
+    StdOther,
+    SchemaError,
+    FileCorrupt,
+    FilePagesCorrupt,
+    SchemaObjectNotFound,
+    TreeModelInvalid,
+    TreeValueTypeMismatch,
+    TreePathNonUnique,
+    TreePathIllegal,
+    TreeOther,
+    /// A native code unknown to this version, e.g. 404 or OBX_NO_SUCCESS
+    Other(i32),
+    /// Raised on the Rust side, without a native code
+    Local,
+}
+
+/// A unique property clashed with an existing object, see Box::put_checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueViolation {
+    /// The name of the unique property
+    pub property: String,
+    /// The id of the object that has the value already
+    pub existing_id: c::obx_id,
+}
+
+impl ErrorKind {
+    pub fn from_code(code: i32) -> ErrorKind {
+        use ErrorKind::*;
+        match u32::try_from(code).unwrap_or(0) {
+            c::OBX_ERROR_ILLEGAL_STATE => IllegalState,
+            c::OBX_ERROR_ILLEGAL_ARGUMENT => IllegalArgument,
+            c::OBX_ERROR_ALLOCATION => Allocation,
+            c::OBX_ERROR_NUMERIC_OVERFLOW => NumericOverflow,
+            c::OBX_ERROR_FEATURE_NOT_AVAILABLE => FeatureNotAvailable,
+            c::OBX_ERROR_SHUTTING_DOWN => ShuttingDown,
+            c::OBX_ERROR_NO_ERROR_INFO => NoErrorInfo,
+            c::OBX_ERROR_GENERAL => General,
+            c::OBX_ERROR_UNKNOWN => Unknown,
+            c::OBX_ERROR_DB_FULL => DbFull,
+            c::OBX_ERROR_MAX_READERS_EXCEEDED => MaxReadersExceeded,
+            c::OBX_ERROR_STORE_MUST_SHUTDOWN => StoreMustShutdown,
+            c::OBX_ERROR_MAX_DATA_SIZE_EXCEEDED => MaxDataSizeExceeded,
+            c::OBX_ERROR_DB_GENERAL => DbGeneral,
+            c::OBX_ERROR_STORAGE_GENERAL => StorageGeneral,
+            c::OBX_ERROR_UNIQUE_VIOLATED => UniqueViolated,
+            c::OBX_ERROR_NON_UNIQUE_RESULT => NonUniqueResult,
+            c::OBX_ERROR_PROPERTY_TYPE_MISMATCH => PropertyTypeMismatch,
+            c::OBX_ERROR_ID_ALREADY_EXISTS => IdAlreadyExists,
+            c::OBX_ERROR_ID_NOT_FOUND => IdNotFound,
+            c::OBX_ERROR_TIME_SERIES => TimeSeries,
+            c::OBX_ERROR_CONSTRAINT_VIOLATED => ConstraintViolated,
+            c::OBX_ERROR_STD_ILLEGAL_ARGUMENT => StdIllegalArgument,
+            c::OBX_ERROR_STD_OUT_OF_RANGE => StdOutOfRange,
+            c::OBX_ERROR_STD_LENGTH => StdLength,
+            c::OBX_ERROR_STD_BAD_ALLOC => StdBadAlloc,
+            c::OBX_ERROR_STD_RANGE => StdRange,
+            c::OBX_ERROR_STD_OVERFLOW => StdOverflow,
+            c::OBX_ERROR_STD_OTHER => StdOther,
+            c::OBX_ERROR_SCHEMA => SchemaError,
+            c::OBX_ERROR_FILE_CORRUPT => FileCorrupt,
+            c::OBX_ERROR_FILE_PAGES_CORRUPT => FilePagesCorrupt,
+            c::OBX_ERROR_SCHEMA_OBJECT_NOT_FOUND => SchemaObjectNotFound,
+            c::OBX_ERROR_TREE_MODEL_INVALID => TreeModelInvalid,
+            c::OBX_ERROR_TREE_VALUE_TYPE_MISMATCH => TreeValueTypeMismatch,
+            c::OBX_ERROR_TREE_PATH_NON_UNIQUE => TreePathNonUnique,
+            c::OBX_ERROR_TREE_PATH_ILLEGAL => TreePathIllegal,
+            c::OBX_ERROR_TREE_OTHER => TreeOther,
+            _ => Other(code),
+        }
+    }
+
+    /// Violated constraints of the data, e.g. a unique index, or an id that exists already
+    pub fn is_constraint(&self) -> bool {
+        use ErrorKind::*;
+        matches!(
+            self,
+            UniqueViolated
+                | NonUniqueResult
+                | PropertyTypeMismatch
+                | IdAlreadyExists
+                | IdNotFound
+                | TimeSeries
+                | ConstraintViolated
+        )
+    }
+
+    /// Failures of the database file, or of its limits
+    pub fn is_storage(&self) -> bool {
+        use ErrorKind::*;
+        matches!(
+            self,
+            DbFull
+                | MaxReadersExceeded
+                | StoreMustShutdown
+                | MaxDataSizeExceeded
+                | DbGeneral
+                | StorageGeneral
+                | FileCorrupt
+                | FilePagesCorrupt
+        )
+    }
+}
+
 impl Error {
     pub fn new_native(native_error: c::NativeError) -> Error {
         Error {
@@ -26,9 +161,51 @@ impl Error {
         }
     }
 
+    pub fn new_unique_violation(property: &str, existing_id: c::obx_id) -> Error {
+        Error {
+            repr: Repr::UniqueViolation(UniqueViolation {
+                property: String::from(property),
+                existing_id,
+            }),
+        }
+    }
+
     pub fn as_result<T>(&self) -> Result<T> {
         Err(self.clone())
     }
+
+    pub fn kind(&self) -> ErrorKind {
+        match &self.repr {
+            Repr::Native(err) => err.kind(),
+            Repr::Local(_) => ErrorKind::Local,
+            Repr::UniqueViolation(_) => ErrorKind::UniqueViolated,
+        }
+    }
+
+    /// The native OBX_ERROR_* code
+    pub fn code(&self) -> Option<i32> {
+        match &self.repr {
+            Repr::Native(err) => Some(err.code()),
+            Repr::Local(_) => None,
+            Repr::UniqueViolation(_) => Some(c::OBX_ERROR_UNIQUE_VIOLATED as i32),
+        }
+    }
+
+    /// The native secondary code, e.g. an errno, zero if there is none
+    pub fn secondary(&self) -> Option<i32> {
+        match &self.repr {
+            Repr::Native(err) => Some(err.secondary()),
+            Repr::Local(_) | Repr::UniqueViolation(_) => None,
+        }
+    }
+
+    /// The clashing property and object, of errors returned by Box::put_checked
+    pub fn unique_violation(&self) -> Option<&UniqueViolation> {
+        match &self.repr {
+            Repr::UniqueViolation(v) => Some(v),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -36,6 +213,11 @@ impl fmt::Display for Error {
         match &self.repr {
             Repr::Native(ref err) => write!(fmt, "{}", err),
             Repr::Local(s) => write!(fmt, "{}", s),
+            Repr::UniqueViolation(v) => write!(
+                fmt,
+                "Error: unique property '{}' is already used by object {}",
+                v.property, v.existing_id
+            ),
         }
     }
 }
@@ -45,6 +227,7 @@ impl fmt::Debug for Repr {
         match &*self {
             Repr::Native(ref err) => fmt::Debug::fmt(&err, fmt),
             Repr::Local(s) => fmt::Debug::fmt(&s, fmt),
+            Repr::UniqueViolation(v) => fmt::Debug::fmt(&v, fmt),
         }
     }
 }
@@ -59,7 +242,7 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self.repr {
             Repr::Native(ref err) => err.source(),
-            Repr::Local(_) => None,
+            Repr::Local(_) | Repr::UniqueViolation(_) => None,
         }
     }
 }
@@ -77,4 +260,33 @@ mod tests {
         let err = Error::new_local("test");
         assert_eq!(format!("{err}"), "test");
     }
+
+    #[test]
+    fn error_kinds() {
+        let err = Error::new_local("test");
+        assert_eq!(ErrorKind::Local, err.kind());
+        assert_eq!(None, err.code());
+        assert_eq!(None, err.secondary());
+
+        assert_eq!(ErrorKind::UniqueViolated, ErrorKind::from_code(10201));
+        assert_eq!(ErrorKind::DbFull, ErrorKind::from_code(10101));
+        assert_eq!(ErrorKind::SchemaError, ErrorKind::from_code(10501));
+        assert_eq!(ErrorKind::Other(404), ErrorKind::from_code(404));
+        assert_eq!(ErrorKind::Other(-1), ErrorKind::from_code(-1));
+
+        assert!(ErrorKind::UniqueViolated.is_constraint());
+        assert!(!ErrorKind::UniqueViolated.is_storage());
+        assert!(ErrorKind::FileCorrupt.is_storage());
+        assert!(!ErrorKind::Local.is_storage());
+    }
+
+    #[test]
+    fn unique_violation() {
+        let err = Error::new_unique_violation("name", 3);
+        assert_eq!(ErrorKind::UniqueViolated, err.kind());
+        assert_eq!(Some(10201), err.code());
+        let v = err.unique_violation().expect("a violation");
+        assert_eq!(("name", 3), (v.property.as_str(), v.existing_id));
+        assert_eq!(None, Error::new_local("test").unique_violation());
+    }
 }
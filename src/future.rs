@@ -0,0 +1,296 @@
+//! Future-returning Box and Query operations, with the "futures" feature.
+//! The operations run on the threads of a WorkerPool, which own the native
+//! handles (transactions, cursors, queries) they use. The futures don't
+//! depend on a runtime, any executor can poll them.
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::c::{self, *};
+use crate::error::{self, Error};
+use crate::query::{Query, SendQuery};
+use crate::r#box;
+use crate::store::Store;
+use crate::traits::{EntityFactoryExt, OBBlanket};
+
+type Job = Box<dyn FnOnce(*mut OBX_store) + Send>;
+
+// The native store is thread-safe, and outlives the workers
+struct StorePtr(*mut OBX_store);
+
+unsafe impl Send for StorePtr {}
+
+impl StorePtr {
+    fn get(&self) -> *mut OBX_store {
+        self.0
+    }
+}
+
+struct Shared<R> {
+    result: Option<error::Result<R>>,
+    waker: Option<Waker>,
+    done: bool,
+}
+
+fn lock<R>(shared: &Mutex<Shared<R>>) -> MutexGuard<'_, Shared<R>> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The result of an operation on a worker, the operation starts right away,
+/// awaiting only waits for it to finish.
+pub struct OpFuture<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+}
+
+impl<R> Future for OpFuture<R> {
+    type Output = error::Result<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = lock(&self.shared);
+        if let Some(result) = shared.result.take() {
+            Poll::Ready(result)
+        } else if shared.done {
+            Poll::Ready(
+                Error::new_local("Error: the operation was dropped by the worker").as_result(),
+            )
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+// Moved into the job, wakes the future when dropped, also when the job panics
+struct Completer<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+}
+
+impl<R> Completer<R> {
+    fn complete(self, result: error::Result<R>) {
+        lock(&self.shared).result = Some(result);
+    }
+}
+
+impl<R> Drop for Completer<R> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut shared = lock(&self.shared);
+            shared.done = true;
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+// Runs the jobs until the pool is dropped, and the queue is empty
+fn work(receiver: &Mutex<mpsc::Receiver<Job>>, obx_store: StorePtr) {
+    loop {
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => break,
+        };
+        match job {
+            Ok(job) => {
+                // a panic only fails its own future
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| job(obx_store.get())));
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+/// Threads that run the operations of the store, in the order they were started.
+/// Dropping the pool waits for the started operations.
+pub struct WorkerPool<'store> {
+    store: &'store Store,
+    sender: Option<mpsc::Sender<Job>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl Drop for WorkerPool<'_> {
+    fn drop(&mut self) {
+        // the workers finish the queue, then stop
+        self.sender = None;
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                eprintln!("Error: worker pool: a worker panicked");
+            }
+        }
+    }
+}
+
+impl<'store> WorkerPool<'store> {
+    /// Starts the given number of worker threads
+    pub fn new(store: &'store Store, threads: usize) -> error::Result<Self> {
+        if threads == 0 {
+            return Error::new_local("Error: a worker pool needs at least one thread").as_result();
+        }
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut pool = WorkerPool {
+            store,
+            sender: Some(sender),
+            workers: Vec::with_capacity(threads),
+        };
+        for i in 0..threads {
+            let receiver = receiver.clone();
+            let obx_store = StorePtr(store.obx_store);
+            let worker = thread::Builder::new()
+                .name(format!("objectbox-worker-{i}"))
+                .spawn(move || work(&receiver, obx_store));
+            match worker {
+                Ok(worker) => pool.workers.push(worker),
+                Err(e) => {
+                    return Error::new_local(&format!("Error: unable to start a worker: {e}"))
+                        .as_result()
+                }
+            }
+        }
+        Ok(pool)
+    }
+
+    pub(crate) fn spawn<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(*mut OBX_store) -> error::Result<R> + Send + 'static,
+    ) -> OpFuture<R> {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+            done: false,
+        }));
+        let completer = Completer {
+            shared: shared.clone(),
+        };
+        if let Some(sender) = &self.sender {
+            // if the send fails, the dropped completer resolves the future
+            let _ = sender.send(Box::new(move |obx_store| completer.complete(f(obx_store))));
+        }
+        OpFuture { shared }
+    }
+
+    /// The operations of T, on this pool
+    pub fn get_box<T: 'static + OBBlanket>(&self) -> error::Result<FutureBox<'_, T>> {
+        Ok(FutureBox {
+            pool: self,
+            helper: self.store.get_box::<T>()?.helper,
+        })
+    }
+
+    /// The operations of a clone of the query, with its current parameters
+    pub fn query<T: 'static + OBBlanket + Send>(
+        &self,
+        query: &Query<T>,
+    ) -> error::Result<FutureQuery<'_, T>> {
+        Ok(FutureQuery {
+            pool: self,
+            query: Arc::new(Mutex::new(query.send_clone()?)),
+        })
+    }
+
+    /// Resolves when the AsyncBox operations submitted so far are written
+    pub fn await_async_submitted(&self) -> OpFuture<bool> {
+        self.spawn(|obx_store| Ok(unsafe { obx_store_await_async_submitted(obx_store) }))
+    }
+
+    /// Resolves when the queue of the AsyncBox operations is empty
+    pub fn await_async_completion(&self) -> OpFuture<bool> {
+        self.spawn(|obx_store| Ok(unsafe { obx_store_await_async_completion(obx_store) }))
+    }
+}
+
+/// Future-returning Box operations, each runs in its own transaction on a worker.
+/// Objects are moved to the worker, and returned with the result.
+pub struct FutureBox<'pool, T> {
+    pool: &'pool WorkerPool<'pool>,
+    helper: Arc<dyn EntityFactoryExt<T>>,
+}
+
+impl<T: 'static + OBBlanket + Send> FutureBox<'_, T> {
+    fn run<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut r#box::Box<T>) -> error::Result<R> + Send + 'static,
+    ) -> OpFuture<R> {
+        let helper = self.helper.clone();
+        self.pool
+            .spawn(move |obx_store| f(&mut r#box::Box::new(obx_store, helper)))
+    }
+
+    /// Resolves to the put object, with its (new) id
+    pub fn put(&self, mut object: T) -> OpFuture<T> {
+        self.run(move |b| b.put(&mut object).map(|_| object))
+    }
+
+    /// Resolves to the put objects, with their (new) ids, all are put in one transaction
+    pub fn put_many(&self, mut objects: Vec<T>) -> OpFuture<Vec<T>> {
+        self.run(move |b| b.put_many(objects.iter_mut().collect()).map(|_| objects))
+    }
+
+    pub fn get(&self, id: c::obx_id) -> OpFuture<Option<T>> {
+        self.run(move |b| b.get(id))
+    }
+
+    pub fn get_many(&self, ids: Vec<c::obx_id>) -> OpFuture<Vec<Option<T>>> {
+        self.run(move |b| b.get_many(&ids))
+    }
+
+    pub fn get_all(&self) -> OpFuture<Vec<T>> {
+        self.run(|b| b.get_all())
+    }
+
+    pub fn contains(&self, id: c::obx_id) -> OpFuture<bool> {
+        self.run(move |b| b.contains(id))
+    }
+
+    pub fn count(&self) -> OpFuture<u64> {
+        self.run(|b| b.count())
+    }
+
+    pub fn remove_with_id(&self, id: c::obx_id) -> OpFuture<bool> {
+        self.run(move |b| b.remove_with_id(id))
+    }
+
+    pub fn remove_all(&self) -> OpFuture<u64> {
+        self.run(|b| b.remove_all())
+    }
+}
+
+/// Future-returning Query operations, on a clone owned by the workers
+pub struct FutureQuery<'pool, T: OBBlanket> {
+    pool: &'pool WorkerPool<'pool>,
+    query: Arc<Mutex<SendQuery<T>>>,
+}
+
+impl<T: 'static + OBBlanket + Send> FutureQuery<'_, T> {
+    fn run<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Query<T>) -> error::Result<R> + Send + 'static,
+    ) -> OpFuture<R> {
+        let query = self.query.clone();
+        self.pool.spawn(move |_| {
+            // one operation at a time, the native query is not thread-safe
+            let query = query.lock().unwrap_or_else(|e| e.into_inner());
+            f(query.get())
+        })
+    }
+
+    pub fn find(&self) -> OpFuture<Vec<T>> {
+        self.run(|q| q.find())
+    }
+
+    pub fn find_ids(&self) -> OpFuture<Vec<c::obx_id>> {
+        self.run(|q| q.find_ids())
+    }
+
+    pub fn count(&self) -> OpFuture<u64> {
+        self.run(|q| q.count())
+    }
+
+    pub fn remove(&self) -> OpFuture<u64> {
+        self.run(|q| q.remove())
+    }
+}
@@ -0,0 +1,79 @@
+//! Streaming iteration over stored objects, see Box::iter and Query::iter.
+//! An iterator holds its own read transaction and cursor, until it's dropped,
+//! so only one object at a time is inflated.
+use std::marker::PhantomData;
+use std::vec;
+
+use crate::c;
+use crate::cursor::Cursor;
+use crate::error;
+use crate::traits::OBBlanket;
+
+enum Source {
+    /// All objects of the box, in id order
+    Walk { started: bool },
+    /// The ids of the matching objects, collected in the same transaction
+    Ids(vec::IntoIter<c::obx_id>),
+}
+
+/// Yields the objects one by one, stops after the first error.
+/// Keep it short-lived, the read transaction stays open until it's dropped.
+pub struct Iter<'a, T: OBBlanket> {
+    cursor: Cursor<T>,
+    source: Source,
+    done: bool,
+    phantom_data: PhantomData<&'a ()>,
+}
+
+impl<T: OBBlanket> Iter<'_, T> {
+    pub(crate) fn walk(cursor: Cursor<T>) -> Self {
+        Iter {
+            cursor,
+            source: Source::Walk { started: false },
+            done: false,
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub(crate) fn from_ids(cursor: Cursor<T>, ids: Vec<c::obx_id>) -> Self {
+        Iter {
+            cursor,
+            source: Source::Ids(ids.into_iter()),
+            done: false,
+            phantom_data: PhantomData,
+        }
+    }
+
+    fn next_entity(&mut self) -> error::Result<Option<T>> {
+        match &mut self.source {
+            Source::Walk { started } => {
+                let first = !*started;
+                *started = true;
+                self.cursor.walk_entity(first)
+            }
+            Source::Ids(ids) => {
+                for id in ids.by_ref() {
+                    if let Some(object) = self.cursor.get_entity(id)? {
+                        return Ok(Some(object));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<T: OBBlanket> Iterator for Iter<'_, T> {
+    type Item = error::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_entity();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+        result.transpose()
+    }
+}
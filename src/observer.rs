@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use std::sync::{mpsc, Mutex};
+
+use crate::c::{self, *};
+use crate::error;
+
+type Callback = Box<dyn FnMut(&[obx_schema_id]) + Send>;
+
+/// Receives the ids of the changed entity types, after each committed write.
+/// The callback runs on a thread of the store, and is closed when dropped.
+/// It can't outlive the store, or the query it was subscribed to.
+pub struct Observer<'store> {
+    obx_observer: *mut OBX_observer,
+    // owned here, the native observer only borrows it
+    callback: *mut Mutex<Callback>,
+    phantom_data: PhantomData<&'store ()>,
+}
+
+impl Drop for Observer<'_> {
+    fn drop(&mut self) {
+        if !self.obx_observer.is_null() {
+            // waits for a running callback, no calls follow
+            if let Err(err) = c::call(unsafe { obx_observer_close(self.obx_observer) }) {
+                eprintln!("Error: observer: {err}");
+            }
+            self.obx_observer = std::ptr::null_mut();
+        }
+        if !self.callback.is_null() {
+            drop(unsafe { Box::from_raw(self.callback) });
+            self.callback = std::ptr::null_mut();
+        }
+    }
+}
+
+unsafe extern "C" fn on_changes(
+    type_ids: *const obx_schema_id,
+    type_ids_count: usize,
+    user_data: *mut c_void,
+) {
+    let ids = if type_ids.is_null() || type_ids_count == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(type_ids, type_ids_count)
+    };
+    let callback = &*(user_data as *const Mutex<Callback>);
+    if let Ok(mut f) = callback.lock() {
+        // don't unwind into the native code
+        if panic::catch_unwind(AssertUnwindSafe(|| f(ids))).is_err() {
+            eprintln!("Error: observer: the callback panicked");
+        }
+    }
+}
+
+// the callback of a single type observer already knows its type id
+unsafe extern "C" fn on_change(user_data: *mut c_void) {
+    on_changes(std::ptr::null(), 0, user_data)
+}
+
+impl Observer<'_> {
+    /// All entity types, or only the given one
+    pub(crate) fn new(
+        store: *mut OBX_store,
+        type_id: Option<obx_schema_id>,
+        mut callback: Callback,
+    ) -> error::Result<Self> {
+        if let Some(id) = type_id {
+            callback = Box::new(move |_| callback(&[id]));
+        }
+        let callback = Box::into_raw(Box::new(Mutex::new(callback)));
+        let user_data = callback as *mut c_void;
+        let obx_observer = unsafe {
+            match type_id {
+                Some(id) => obx_observe_single_type(store, id, Some(on_change), user_data),
+                None => obx_observe(store, Some(on_changes), user_data),
+            }
+        };
+        // dropping the observer frees the callback, also on error
+        let mut observer = Observer {
+            obx_observer: std::ptr::null_mut(),
+            callback,
+            phantom_data: PhantomData,
+        };
+        observer.obx_observer = c::new_mut(obx_observer)?;
+        Ok(observer)
+    }
+
+    /// Calls back right away, like after a committed write
+    pub(crate) fn trigger(&self) {
+        unsafe { on_changes(std::ptr::null(), 0, self.callback as *mut c_void) }
+    }
+
+    /// Forwards the entity ids, the receiver is disconnected when the observer is dropped
+    pub(crate) fn new_channel(
+        store: *mut OBX_store,
+        type_id: Option<obx_schema_id>,
+    ) -> error::Result<(Self, mpsc::Receiver<obx_schema_id>)> {
+        let (sender, receiver) = mpsc::channel();
+        let observer = Self::new(
+            store,
+            type_id,
+            Box::new(move |ids| {
+                for id in ids {
+                    let _ = sender.send(*id);
+                }
+            }),
+        )?;
+        Ok((observer, receiver))
+    }
+}
@@ -1,11 +1,14 @@
 #![allow(dead_code)]
-use std::ffi::{c_uint, CStr};
+use std::ffi::{c_uint, CStr, CString};
 use std::path::Path;
 
 use crate::model::Model;
-use crate::util::{ToCChar, ToCVoid};
+use crate::util::ToCVoid;
 use crate::{c::*, error};
 
+/// The directory prefix of in-memory stores
+pub const MEMORY_PREFIX: &str = "memory:";
+
 pub struct Opt {
     pub(crate) obx_opt: *mut OBX_store_options,
     pub(crate) ptr_consumed: bool,
@@ -46,7 +49,24 @@ impl Opt {
     }
 
     pub fn directory(&self, dir: &Path) -> error::Result<&Self> {
-        call(unsafe { obx_opt_directory(self.obx_opt, dir.as_c_char_ptr()) }).map(|_| self)
+        match dir.to_str() {
+            Some(dir) => self.set_directory(dir),
+            None => error::Error::new_local("Error: unable to parse the directory").as_result(),
+        }
+    }
+
+    /// A store that only lives in memory, stores with the same name share the data
+    pub fn in_memory(&self, name: &str) -> error::Result<&Self> {
+        self.set_directory(&format!("{MEMORY_PREFIX}{name}"))
+    }
+
+    fn set_directory(&self, dir: &str) -> error::Result<&Self> {
+        // the string must outlive the call
+        if let Ok(dir) = CString::new(dir) {
+            call(unsafe { obx_opt_directory(self.obx_opt, dir.as_ptr()) }).map(|_| self)
+        } else {
+            error::Error::new_local("Error: unable to parse the directory").as_result()
+        }
     }
 
     pub fn max_db_size_in_kb(&self, size_in_kb: u64) -> &Self {
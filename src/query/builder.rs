@@ -2,7 +2,7 @@
 use std::{
     ffi::{CStr, CString},
     marker::PhantomData,
-    rc::Rc,
+    sync::Arc,
 };
 
 // TODO also error check before chaining the next call (obx_qb_cond)
@@ -17,11 +17,13 @@ use crate::{
 };
 
 use super::condition::Condition;
+use super::link::Link;
 use crate::query::Query;
 
 impl<T: OBBlanket> Drop for Builder<T> {
     fn drop(&mut self) {
-        if !self.has_built_query && !self.obx_query_builder.is_null() {
+        // the sub-builder of a link is closed with its root
+        if !self.has_built_query && self.helper.is_some() && !self.obx_query_builder.is_null() {
             if let Err(err) = self.close() {
                 eprintln!("Error: async: {err}");
             }
@@ -32,7 +34,8 @@ impl<T: OBBlanket> Drop for Builder<T> {
 
 pub struct Builder<T: OBBlanket> {
     obx_store: *mut OBX_store,
-    helper: Rc<dyn EntityFactoryExt<T>>,
+    // None for the sub-builder of a link
+    helper: Option<Arc<dyn EntityFactoryExt<T>>>,
     property_id: obx_schema_id,
     obx_query_builder: *mut OBX_query_builder,
     case_sensitive: bool,
@@ -44,25 +47,53 @@ pub struct Builder<T: OBBlanket> {
 /// if a condition from another table is passed
 impl<T: OBBlanket> Builder<T> {
     pub(crate) fn new(box_store: &Box<T>, condition: &mut Condition<T>) -> error::Result<Self> {
+        let mut builder = Self::new_empty(box_store)?;
+        builder.add_conditions(condition)?;
+        c::get_result(builder.error_code(), builder)
+    }
+
+    // without conditions on T, e.g. only on linked entities
+    pub(crate) fn new_empty(box_store: &Box<T>) -> error::Result<Self> {
         let entity_id = box_store.helper.get_entity_id(); // call factory
         let obx_store = box_store.get_store();
         new_mut(obx_store)?;
         let obx_query_builder = unsafe { obx_query_builder(obx_store, entity_id) };
         new_mut(obx_query_builder)?;
 
-        let mut builder = Builder {
+        Ok(Builder {
             obx_store,
-            helper: box_store.helper.clone(),
+            helper: Some(box_store.helper.clone()),
             property_id: 0,
             obx_query_builder,
             has_built_query: false,
             case_sensitive: false,
             phantom_data: PhantomData,
-        };
+        })
+    }
 
-        condition.visit_dfs(&mut |c| builder.get_condition_integer(c));
+    /// Put conditions on the objects related through link,
+    /// only the objects of T with matching related objects are found.
+    pub fn link<Target: OBBlanket>(
+        &mut self,
+        link: &Link<T, Target>,
+        condition: &mut Condition<Target>,
+    ) -> error::Result<&mut Self> {
+        let obx_link_builder = unsafe { link.kind.obx_link(self.obx_query_builder) };
+        new_mut(obx_link_builder)?;
+
+        let mut sub_builder = Builder::<Target> {
+            obx_store: self.obx_store,
+            helper: None,
+            property_id: 0,
+            obx_query_builder: obx_link_builder,
+            has_built_query: false,
+            case_sensitive: false,
+            phantom_data: PhantomData,
+        };
+        sub_builder.add_conditions(condition)?;
 
-        c::get_result(builder.error_code(), builder)
+        c::call(sub_builder.error_code())?;
+        c::call(self.error_code()).map(|_| self)
     }
 
     fn get_group_integer(&self, c: &Condition<T>, mut f: impl FnMut(*const i32, usize) -> c::obx_qb_cond) -> c::obx_qb_cond {
@@ -78,7 +109,22 @@ impl<T: OBBlanket> Builder<T> {
         }
     }
 
-    fn get_condition_integer(&mut self, c: &mut Condition<T>) -> c::obx_qb_cond {
+    // the first error stops adding further conditions
+    fn add_conditions(&mut self, condition: &mut Condition<T>) -> error::Result<()> {
+        let mut result = Ok(());
+        condition.visit_dfs(&mut |c| {
+            if result.is_err() {
+                return QUERY_NO_OP;
+            }
+            self.get_condition_integer(c).unwrap_or_else(|err| {
+                result = Err(err);
+                QUERY_NO_OP
+            })
+        });
+        result
+    }
+
+    fn get_condition_integer(&mut self, c: &mut Condition<T>) -> error::Result<c::obx_qb_cond> {
         // invariant to 0 parameter condition functions
         self.property_id = c.get_property_id();
 
@@ -177,7 +223,7 @@ impl<T: OBBlanket> Builder<T> {
                             //     "Bad string conversion (in_strings: {})",
                             //     err.to_string()
                             // ))?;
-                            return QUERY_NO_OP;
+                            return Ok(QUERY_NO_OP);
                         }
                     }
                     let vec: Vec<_> = new_strings
@@ -191,13 +237,25 @@ impl<T: OBBlanket> Builder<T> {
                 }
                 ConditionOp::NoOp => QUERY_NO_OP,
             };
-            result
+            // the alias applies to the condition that was just created
+            if result != QUERY_NO_OP {
+                if let Some(alias) = &c.alias {
+                    self.param_alias(alias)?;
+                }
+            }
+            Ok(result)
         }
     }
 
     /// Why does Self::build have to be called separately?
     pub fn build(&mut self) -> error::Result<Query<T>> {
-        let query = Query::new(self.obx_store, self.helper.clone(), self.obx_query_builder)?;
+        let helper = match &self.helper {
+            Some(h) => h.clone(),
+            None => {
+                error::Error::new_local("Error: a link can't be built on its own").as_result()?
+            }
+        };
+        let query = Query::new(self.obx_store, helper, self.obx_query_builder)?;
         // iff a query is built properly, then do not drop, else drop
         self.has_built_query = true;
         Ok(query)
@@ -223,6 +281,15 @@ impl<T: OBBlanket> Builder<T> {
         unsafe { obx_qb_error_message(self.obx_query_builder) }
     }
 
+    // native failures are picked up by error_code
+    unsafe fn param_alias(&mut self, alias: &str) -> error::Result<()> {
+        let c_alias = CString::new(alias).map_err(|err| {
+            error::Error::new_local(&format!("Error: invalid alias '{alias}': {err}"))
+        })?;
+        obx_qb_param_alias(self.obx_query_builder, c_alias.as_ptr());
+        Ok(())
+    }
+
     // TODO implement Option<*> properties, or this will always return false
     unsafe fn is_null(&mut self) -> obx_qb_cond {
         obx_qb_null(self.obx_query_builder, self.property_id)
@@ -21,6 +21,7 @@ pub struct Condition<Entity: OBBlanket> {
     // to the other enum values. Now we have a (directional) tree.
     pub(crate) group: Option<Vec<Self>>,
     pub(crate) result: Option<c::obx_qb_cond>,
+    pub(crate) alias: Option<String>,
 }
 
 impl<Entity: OBBlanket> Condition<Entity> {
@@ -39,6 +40,7 @@ impl<Entity: OBBlanket> Condition<Entity> {
             op,
             group: Some(group),
             result: None,
+            alias: None,
         }
     }
 
@@ -49,9 +51,17 @@ impl<Entity: OBBlanket> Condition<Entity> {
             op,
             group: None,
             result: None,
+            alias: None,
         }
     }
 
+    /// Name this condition, so its value can be replaced
+    /// on the built query with Query::set_alias
+    pub fn alias(mut self, name: &str) -> Self {
+        self.alias = Some(name.to_string());
+        self
+    }
+
     pub fn or(self, that: Condition<Entity>) -> Self {
         Self::new_group(
             self.ids_and_type.clone(),
@@ -127,7 +137,7 @@ mod tests {
 
     use crate::{
         c,
-        traits::{FBOBBridge, IdExt},
+        traits::{FBOBBridge, IdExt, RelationExt},
     };
 
     struct SomeEntity {
@@ -148,6 +158,8 @@ mod tests {
         }
     }
 
+    impl RelationExt for SomeEntity {}
+
     #[test]
     fn query_bitandor_overload() {
         let it = IdsAndType::new((0, 1, 2));
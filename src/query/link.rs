@@ -0,0 +1,86 @@
+use std::marker::PhantomData;
+
+use crate::c::{self, *};
+use crate::traits::OBBlanket;
+
+#[derive(Clone, Copy)]
+pub(crate) enum LinkKind {
+    // source entity id, relation property id
+    Property(obx_schema_id, obx_schema_id),
+    Backlink(obx_schema_id, obx_schema_id),
+    // relation id
+    Standalone(obx_schema_id),
+    BacklinkStandalone(obx_schema_id),
+}
+
+/// A typed relation from the queried Source to a Target entity,
+/// used to put conditions on the related objects with Builder::link.
+pub struct Link<Source: OBBlanket, Target: OBBlanket> {
+    pub(crate) kind: LinkKind,
+    phantom_data: PhantomData<(Source, Target)>,
+}
+
+impl<Source: OBBlanket, Target: OBBlanket> Link<Source, Target> {
+    fn new(kind: LinkKind) -> Self {
+        Link {
+            kind,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Many-to-one, via a relation property of Source
+    pub(crate) fn to_one(source_entity_id: obx_schema_id, property_id: obx_schema_id) -> Self {
+        Self::new(LinkKind::Property(source_entity_id, property_id))
+    }
+
+    /// Many-to-many, via a standalone relation of Source
+    pub(crate) fn to_many(relation_id: obx_schema_id) -> Self {
+        Self::new(LinkKind::Standalone(relation_id))
+    }
+
+    /// Only for the links of the generated condition factories
+    ///
+    /// # Safety
+    /// The ids must be those of a relation property of Source that targets Target
+    #[doc(hidden)]
+    pub unsafe fn generated_to_one(
+        source_entity_id: obx_schema_id,
+        property_id: obx_schema_id,
+    ) -> Self {
+        Self::to_one(source_entity_id, property_id)
+    }
+
+    /// Only for the links of the generated condition factories
+    ///
+    /// # Safety
+    /// The id must be that of a standalone relation from Source to Target
+    #[doc(hidden)]
+    pub unsafe fn generated_to_many(relation_id: obx_schema_id) -> Self {
+        Self::to_many(relation_id)
+    }
+
+    /// The same relation in reverse, from Target to Source
+    pub fn backlink(&self) -> Link<Target, Source> {
+        Link::new(match self.kind {
+            LinkKind::Property(e, p) => LinkKind::Backlink(e, p),
+            LinkKind::Backlink(e, p) => LinkKind::Property(e, p),
+            LinkKind::Standalone(r) => LinkKind::BacklinkStandalone(r),
+            LinkKind::BacklinkStandalone(r) => LinkKind::Standalone(r),
+        })
+    }
+}
+
+impl LinkKind {
+    // the returned sub-builder is owned by the given builder, never close it
+    pub(crate) unsafe fn obx_link(
+        &self,
+        builder: *mut OBX_query_builder,
+    ) -> *mut OBX_query_builder {
+        match *self {
+            LinkKind::Property(_, p) => c::obx_qb_link_property(builder, p),
+            LinkKind::Backlink(e, p) => c::obx_qb_backlink_property(builder, e, p),
+            LinkKind::Standalone(r) => c::obx_qb_link_standalone(builder, r),
+            LinkKind::BacklinkStandalone(r) => c::obx_qb_backlink_standalone(builder, r),
+        }
+    }
+}
@@ -2,6 +2,9 @@
 pub(crate) mod builder;
 pub mod condition;
 pub(crate) mod enums;
+pub mod link;
+pub mod param;
+pub mod property;
 mod query;
 pub mod traits;
 
@@ -0,0 +1,176 @@
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
+
+use crate::c::{self, *};
+use crate::error;
+use crate::traits::OBBlanket;
+
+use super::traits::{BetweenExt, EqExt, InOutExt, OrdExt};
+
+/// Where a parameter value goes, either the condition on a property
+/// (the property must only have a single condition) or an aliased condition
+pub enum ParamTarget<'a> {
+    Property(obx_schema_id, obx_schema_id),
+    Alias(&'a str),
+}
+
+/// A value that can replace the value of a condition in a built query
+pub trait ParamValue {
+    /// # Safety
+    /// query must be an open query
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()>;
+}
+
+/// Implemented by the generated condition factory fields,
+/// when their conditions take a value of type V
+pub trait ParamProperty<Entity, V> {}
+
+impl<E: OBBlanket, P: ?Sized + EqExt<E, i64>> ParamProperty<E, i64> for P {}
+impl<E: OBBlanket, P: ?Sized + BetweenExt<E, i64>> ParamProperty<E, (i64, i64)> for P {}
+impl<E: OBBlanket, P: ?Sized + InOutExt<E, i64>> ParamProperty<E, Vec<i64>> for P {}
+impl<E: OBBlanket, P: ?Sized + InOutExt<E, i32>> ParamProperty<E, Vec<i32>> for P {}
+impl<E: OBBlanket, P: ?Sized + OrdExt<E, f64>> ParamProperty<E, f64> for P {}
+impl<E: OBBlanket, P: ?Sized + BetweenExt<E, f64>> ParamProperty<E, (f64, f64)> for P {}
+impl<E: OBBlanket, P: ?Sized + EqExt<E, String>> ParamProperty<E, &str> for P {}
+impl<E: OBBlanket, P: ?Sized + EqExt<E, String>> ParamProperty<E, String> for P {}
+impl<E: OBBlanket, P: ?Sized + InOutExt<E, String>> ParamProperty<E, Vec<String>> for P {}
+impl<E: OBBlanket, P: ?Sized + EqExt<E, Vec<u8>>> ParamProperty<E, &[u8]> for P {}
+impl<E: OBBlanket, P: ?Sized + EqExt<E, Vec<u8>>> ParamProperty<E, Vec<u8>> for P {}
+
+fn to_c_string(s: &str) -> error::Result<CString> {
+    CString::new(s).map_err(|err| error::Error::new_local(&err.to_string()))
+}
+
+impl ParamValue for i64 {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        c::call(match target {
+            ParamTarget::Property(e, p) => obx_query_param_int(query, *e, *p, *self),
+            ParamTarget::Alias(a) => {
+                obx_query_param_alias_int(query, to_c_string(a)?.as_ptr(), *self)
+            }
+        })
+    }
+}
+
+impl ParamValue for (i64, i64) {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        c::call(match target {
+            ParamTarget::Property(e, p) => obx_query_param_2ints(query, *e, *p, self.0, self.1),
+            ParamTarget::Alias(a) => {
+                obx_query_param_alias_2ints(query, to_c_string(a)?.as_ptr(), self.0, self.1)
+            }
+        })
+    }
+}
+
+impl ParamValue for Vec<i64> {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        c::call(match target {
+            ParamTarget::Property(e, p) => {
+                obx_query_param_int64s(query, *e, *p, self.as_ptr(), self.len())
+            }
+            ParamTarget::Alias(a) => obx_query_param_alias_int64s(
+                query,
+                to_c_string(a)?.as_ptr(),
+                self.as_ptr(),
+                self.len(),
+            ),
+        })
+    }
+}
+
+impl ParamValue for Vec<i32> {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        c::call(match target {
+            ParamTarget::Property(e, p) => {
+                obx_query_param_int32s(query, *e, *p, self.as_ptr(), self.len())
+            }
+            ParamTarget::Alias(a) => obx_query_param_alias_int32s(
+                query,
+                to_c_string(a)?.as_ptr(),
+                self.as_ptr(),
+                self.len(),
+            ),
+        })
+    }
+}
+
+impl ParamValue for f64 {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        c::call(match target {
+            ParamTarget::Property(e, p) => obx_query_param_double(query, *e, *p, *self),
+            ParamTarget::Alias(a) => {
+                obx_query_param_alias_double(query, to_c_string(a)?.as_ptr(), *self)
+            }
+        })
+    }
+}
+
+impl ParamValue for (f64, f64) {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        c::call(match target {
+            ParamTarget::Property(e, p) => obx_query_param_2doubles(query, *e, *p, self.0, self.1),
+            ParamTarget::Alias(a) => {
+                obx_query_param_alias_2doubles(query, to_c_string(a)?.as_ptr(), self.0, self.1)
+            }
+        })
+    }
+}
+
+impl ParamValue for &str {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        let value = to_c_string(self)?;
+        c::call(match target {
+            ParamTarget::Property(e, p) => obx_query_param_string(query, *e, *p, value.as_ptr()),
+            ParamTarget::Alias(a) => {
+                obx_query_param_alias_string(query, to_c_string(a)?.as_ptr(), value.as_ptr())
+            }
+        })
+    }
+}
+
+impl ParamValue for String {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        self.as_str().set_param(query, target)
+    }
+}
+
+impl ParamValue for Vec<String> {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        // the CStrings have to outlive the array of pointers
+        let values = self
+            .iter()
+            .map(|s| to_c_string(s))
+            .collect::<error::Result<Vec<CString>>>()?;
+        let ptrs: Vec<*const c_char> = values.iter().map(|s| s.as_ptr()).collect();
+        c::call(match target {
+            ParamTarget::Property(e, p) => {
+                obx_query_param_strings(query, *e, *p, ptrs.as_ptr(), ptrs.len())
+            }
+            ParamTarget::Alias(a) => obx_query_param_alias_strings(
+                query,
+                to_c_string(a)?.as_ptr(),
+                ptrs.as_ptr(),
+                ptrs.len(),
+            ),
+        })
+    }
+}
+
+impl ParamValue for &[u8] {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        let ptr = self.as_ptr() as *const c_void;
+        c::call(match target {
+            ParamTarget::Property(e, p) => obx_query_param_bytes(query, *e, *p, ptr, self.len()),
+            ParamTarget::Alias(a) => {
+                obx_query_param_alias_bytes(query, to_c_string(a)?.as_ptr(), ptr, self.len())
+            }
+        })
+    }
+}
+
+impl ParamValue for Vec<u8> {
+    unsafe fn set_param(&self, query: *mut OBX_query, target: &ParamTarget) -> error::Result<()> {
+        self.as_slice().set_param(query, target)
+    }
+}
@@ -0,0 +1,359 @@
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::{ptr, slice};
+
+use crate::c::{self, *};
+use crate::error;
+use crate::traits::OBBlanket;
+
+use super::traits::{
+    CharBlanket, F32Blanket, F64Blanket, I16Blanket, I32Blanket, I64Blanket, I8Blanket,
+    StringBlanket,
+};
+use super::Query;
+
+/// The value type of min, max and sum, the native side
+/// only aggregates into i64 (integers) or f64 (floating point)
+pub trait AggregateValue: Copy + Default {
+    /// # Safety
+    /// query must be an open property query
+    unsafe fn obx_min(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err;
+    /// # Safety
+    /// query must be an open property query
+    unsafe fn obx_max(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err;
+    /// # Safety
+    /// query must be an open property query
+    unsafe fn obx_sum(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err;
+}
+
+impl AggregateValue for i64 {
+    unsafe fn obx_min(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err {
+        obx_query_prop_min_int(query, out, out_count)
+    }
+    unsafe fn obx_max(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err {
+        obx_query_prop_max_int(query, out, out_count)
+    }
+    unsafe fn obx_sum(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err {
+        obx_query_prop_sum_int(query, out, out_count)
+    }
+}
+
+impl AggregateValue for f64 {
+    unsafe fn obx_min(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err {
+        obx_query_prop_min(query, out, out_count)
+    }
+    unsafe fn obx_max(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err {
+        obx_query_prop_max(query, out, out_count)
+    }
+    unsafe fn obx_sum(query: *mut OBX_query_prop, out: *mut Self, out_count: *mut i64) -> obx_err {
+        obx_query_prop_sum(query, out, out_count)
+    }
+}
+
+/// Implemented by the numeric blankets of the generated condition factory fields,
+/// so only numeric properties can be aggregated
+pub trait NumericProperty {
+    type Value: AggregateValue;
+}
+
+impl<T: OBBlanket> NumericProperty for dyn I64Blanket<T> {
+    type Value = i64;
+}
+impl<T: OBBlanket> NumericProperty for dyn I32Blanket<T> {
+    type Value = i64;
+}
+impl<T: OBBlanket> NumericProperty for dyn I16Blanket<T> {
+    type Value = i64;
+}
+impl<T: OBBlanket> NumericProperty for dyn I8Blanket<T> {
+    type Value = i64;
+}
+impl<T: OBBlanket> NumericProperty for dyn CharBlanket<T> {
+    type Value = i64;
+}
+impl<T: OBBlanket> NumericProperty for dyn F64Blanket<T> {
+    type Value = f64;
+}
+impl<T: OBBlanket> NumericProperty for dyn F32Blanket<T> {
+    type Value = f64;
+}
+
+/// The element type of find_values, one per native find function
+pub trait FindValue: Sized {
+    /// # Safety
+    /// query must be an open property query
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>>;
+}
+
+// copy the native array, then free it
+unsafe fn take_array<A, V: Copy>(
+    array: *mut A,
+    items: impl FnOnce(&A) -> (*const V, usize),
+    free: unsafe extern "C" fn(*mut A),
+) -> error::Result<Vec<V>> {
+    let array = c::new_mut(array)?;
+    let (ptr, count) = items(&*array);
+    let vec = if count == 0 {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(ptr, count).to_vec()
+    };
+    free(array);
+    Ok(vec)
+}
+
+fn null_or<V>(value_if_null: Option<&V>) -> *const V {
+    value_if_null.map_or(ptr::null(), |v| v as *const V)
+}
+
+impl FindValue for i64 {
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>> {
+        take_array(
+            obx_query_prop_find_int64s(query, null_or(value_if_null)),
+            |a| (a.items, a.count),
+            obx_int64_array_free,
+        )
+    }
+}
+
+impl FindValue for i32 {
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>> {
+        take_array(
+            obx_query_prop_find_int32s(query, null_or(value_if_null)),
+            |a| (a.items, a.count),
+            obx_int32_array_free,
+        )
+    }
+}
+
+impl FindValue for i16 {
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>> {
+        take_array(
+            obx_query_prop_find_int16s(query, null_or(value_if_null)),
+            |a| (a.items, a.count),
+            obx_int16_array_free,
+        )
+    }
+}
+
+impl FindValue for i8 {
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>> {
+        take_array(
+            obx_query_prop_find_int8s(query, null_or(value_if_null)),
+            |a| (a.items, a.count),
+            obx_int8_array_free,
+        )
+    }
+}
+
+impl FindValue for f64 {
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>> {
+        take_array(
+            obx_query_prop_find_doubles(query, null_or(value_if_null)),
+            |a| (a.items, a.count),
+            obx_double_array_free,
+        )
+    }
+}
+
+impl FindValue for f32 {
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>> {
+        take_array(
+            obx_query_prop_find_floats(query, null_or(value_if_null)),
+            |a| (a.items, a.count),
+            obx_float_array_free,
+        )
+    }
+}
+
+impl FindValue for String {
+    unsafe fn obx_find(
+        query: *mut OBX_query_prop,
+        value_if_null: Option<&Self>,
+    ) -> error::Result<Vec<Self>> {
+        // keep the CString alive until the call returns
+        let c_value_if_null = match value_if_null {
+            Some(s) => Some(
+                CString::new(s.as_str())
+                    .map_err(|err| error::Error::new_local(&err.to_string()))?,
+            ),
+            None => None,
+        };
+        let c_ptr = c_value_if_null.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+        let array = c::new_mut(obx_query_prop_find_strings(query, c_ptr))?;
+        let a = &*array;
+        let mut vec = Vec::with_capacity(a.count);
+        if a.count > 0 {
+            for item in slice::from_raw_parts(a.items, a.count) {
+                vec.push(CStr::from_ptr(*item).to_string_lossy().into_owned());
+            }
+        }
+        obx_string_array_free(array);
+        Ok(vec)
+    }
+}
+
+/// Implemented by the blankets of the generated condition factory fields
+/// that can be read as a column, maps the field to its element type
+pub trait ValueProperty {
+    type Item: FindValue;
+}
+
+impl<T: OBBlanket> ValueProperty for dyn I64Blanket<T> {
+    type Item = i64;
+}
+impl<T: OBBlanket> ValueProperty for dyn I32Blanket<T> {
+    type Item = i32;
+}
+impl<T: OBBlanket> ValueProperty for dyn I16Blanket<T> {
+    type Item = i16;
+}
+impl<T: OBBlanket> ValueProperty for dyn I8Blanket<T> {
+    type Item = i8;
+}
+impl<T: OBBlanket> ValueProperty for dyn F64Blanket<T> {
+    type Item = f64;
+}
+impl<T: OBBlanket> ValueProperty for dyn F32Blanket<T> {
+    type Item = f32;
+}
+impl<T: OBBlanket> ValueProperty for dyn StringBlanket<T> {
+    type Item = String;
+}
+
+/// A query on a single property of the entities matched by a Query.
+/// P is the blanket of the condition factory field, it decides
+/// which operations are available.
+pub struct PropertyQuery<'a, T: OBBlanket, P: ?Sized> {
+    obx_query_prop: *mut OBX_query_prop,
+    phantom_data: PhantomData<(&'a Query<T>, *const P)>,
+}
+
+impl<T: OBBlanket, P: ?Sized> Drop for PropertyQuery<'_, T, P> {
+    fn drop(&mut self) {
+        if !self.obx_query_prop.is_null() {
+            if let Err(err) = c::call(unsafe { obx_query_prop_close(self.obx_query_prop) }) {
+                eprintln!("Error: query prop: {err}");
+            }
+            self.obx_query_prop = ptr::null_mut();
+        }
+    }
+}
+
+impl<'a, T: OBBlanket, P: ?Sized> PropertyQuery<'a, T, P> {
+    // the query must outlive the property query, hence the lifetime
+    pub(crate) fn new(
+        obx_query: *mut OBX_query,
+        property_id: obx_schema_id,
+    ) -> error::Result<Self> {
+        c::new_mut(unsafe { obx_query_prop(obx_query, property_id) }).map(|obx_query_prop| {
+            PropertyQuery {
+                obx_query_prop,
+                phantom_data: PhantomData,
+            }
+        })
+    }
+
+    /// Only consider distinct values, affects count and the aggregates
+    pub fn distinct(&self, distinct: bool) -> error::Result<&Self> {
+        c::call(unsafe { obx_query_prop_distinct(self.obx_query_prop, distinct) }).map(|_| self)
+    }
+
+    /// Counts the non-null values of the property
+    pub fn count(&self) -> error::Result<u64> {
+        let mut count = 0;
+        c::call(unsafe { obx_query_prop_count(self.obx_query_prop, &mut count) }).map(|_| count)
+    }
+
+    // None if there were no values to aggregate
+    fn aggregate<V>(
+        &self,
+        f: unsafe fn(*mut OBX_query_prop, *mut V, *mut i64) -> obx_err,
+    ) -> error::Result<Option<V>>
+    where
+        V: Copy + Default,
+    {
+        let mut value = V::default();
+        let mut count = 0;
+        c::call(unsafe { f(self.obx_query_prop, &mut value, &mut count) }).map(|_| {
+            if count > 0 {
+                Some(value)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl<'a, T: OBBlanket, P: ?Sized + NumericProperty> PropertyQuery<'a, T, P> {
+    pub fn min(&self) -> error::Result<Option<P::Value>> {
+        self.aggregate(P::Value::obx_min)
+    }
+
+    pub fn max(&self) -> error::Result<Option<P::Value>> {
+        self.aggregate(P::Value::obx_max)
+    }
+
+    /// The sum of no values is zero
+    pub fn sum(&self) -> error::Result<P::Value> {
+        self.aggregate(P::Value::obx_sum)
+            .map(|sum| sum.unwrap_or_default())
+    }
+
+    pub fn avg(&self) -> error::Result<Option<f64>> {
+        self.aggregate(|query, out, out_count| unsafe { obx_query_prop_avg(query, out, out_count) })
+    }
+}
+
+impl<'a, T: OBBlanket, P: ?Sized + NumericProperty<Value = i64>> PropertyQuery<'a, T, P> {
+    /// The average rounded to an integer, without the precision loss of f64 on large values
+    pub fn avg_int(&self) -> error::Result<Option<i64>> {
+        self.aggregate(|query, out, out_count| unsafe {
+            obx_query_prop_avg_int(query, out, out_count)
+        })
+    }
+}
+
+impl<'a, T: OBBlanket, P: ?Sized + ValueProperty> PropertyQuery<'a, T, P> {
+    /// Reads the property of all matched objects, without inflating the objects.
+    /// Null values are skipped, honors distinct.
+    pub fn find_values(&self) -> error::Result<Vec<P::Item>> {
+        unsafe { P::Item::obx_find(self.obx_query_prop, None) }
+    }
+
+    /// Same as find_values, but null values are replaced by value_if_null
+    pub fn find_values_or(&self, value_if_null: P::Item) -> error::Result<Vec<P::Item>> {
+        unsafe { P::Item::obx_find(self.obx_query_prop, Some(&value_if_null)) }
+    }
+}
+
+impl<'a, T: OBBlanket> PropertyQuery<'a, T, dyn StringBlanket<T>> {
+    /// Only consider distinct strings, optionally ignoring case
+    pub fn distinct_case(&self, distinct: bool, case_sensitive: bool) -> error::Result<&Self> {
+        c::call(unsafe {
+            obx_query_prop_distinct_case(self.obx_query_prop, distinct, case_sensitive)
+        })
+        .map(|_| self)
+    }
+}
@@ -1,14 +1,50 @@
 use crate::c;
 use crate::c::*;
-use crate::cursor::Cursor;
+use crate::cursor::{object_from_data, ref_from_data, Cursor};
 use crate::error;
+use crate::iter::Iter;
+use crate::observer::Observer;
+use crate::query::param::{ParamProperty, ParamTarget, ParamValue};
+use crate::query::property::PropertyQuery;
+use crate::query::traits::BasicExt;
 use crate::traits::EntityFactoryExt;
 use crate::traits::OBBlanket;
+use crate::traits::RefExt;
+use crate::txn::TxScope;
 use crate::util::test_fn_ptr_on_char_ptr;
 use core::slice;
+use std::any::Any;
 use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
-use std::rc::Rc;
+use std::sync::Arc;
+
+// The closure of for_each_ref, and a panic to resume after the visit
+struct RefVisitor<F> {
+    f: F,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+unsafe extern "C" fn visit_ref<T, F>(
+    data: *const std::os::raw::c_void,
+    size: usize,
+    user_data: *mut std::os::raw::c_void,
+) -> bool
+where
+    T: RefExt,
+    F: FnMut(T::Ref<'_>) -> bool,
+{
+    let visitor = &mut *(user_data as *mut RefVisitor<F>);
+    let data = slice::from_raw_parts(data as *const u8, size);
+    // don't unwind into the native code
+    match panic::catch_unwind(AssertUnwindSafe(|| (visitor.f)(ref_from_data::<T>(data)))) {
+        Ok(go_on) => go_on,
+        Err(payload) => {
+            visitor.panic = Some(payload);
+            false
+        }
+    }
+}
 
 // TODO pass generic type from box, via fn
 impl<T: OBBlanket> Drop for Query<T> {
@@ -26,14 +62,14 @@ impl<T: OBBlanket> Drop for Query<T> {
 pub struct Query<T: OBBlanket> {
     obx_query: *mut OBX_query,
     obx_store: *mut OBX_store,
-    pub(crate) helper: Rc<dyn EntityFactoryExt<T>>,
+    pub(crate) helper: Arc<dyn EntityFactoryExt<T>>,
     phantom_data: PhantomData<T>,
 }
 
 impl<T: OBBlanket> Query<T> {
     pub(crate) fn new(
         obx_store: *mut OBX_store,
-        helper: Rc<dyn EntityFactoryExt<T>>,
+        helper: Arc<dyn EntityFactoryExt<T>>,
         builder: *mut OBX_query_builder,
     ) -> error::Result<Self> {
         unsafe {
@@ -144,6 +180,16 @@ impl<T: OBBlanket> Query<T> {
     //     obx_query_remove(self.obx_query, out_count)
     // }
 
+    /// Query a single property of the matched objects, e.g. for aggregates.
+    /// Takes a field of the generated condition factory.
+    pub fn property<P: ?Sized + BasicExt<T>>(
+        &self,
+        property: &impl AsRef<P>,
+    ) -> error::Result<PropertyQuery<'_, T, P>> {
+        let (_, property_id, _) = *property.as_ref().get_property_attrs();
+        PropertyQuery::new(self.obx_query, property_id)
+    }
+
     /// For testing and debugging
     /// A function pointer is passed here, to prevent dealing with lifetime issues.
     pub fn describe(&self, fn_ptr: fn(String) -> bool) -> bool {
@@ -183,9 +229,36 @@ impl<T: OBBlanket> Query<T> {
     // and shares a PR, and improves on this
     // by calling obx_query_cursor_find
     pub fn find(&self) -> error::Result<Vec<T>> {
-        let mut vec = Vec::new();
         let mut cursor = Cursor::new(false, self.obx_store, self.helper.clone())?;
-        let ids = self.find_ids()?;
+        self.find_with_cursor(&mut cursor)
+    }
+
+    /// Same as find, but reads inside the given (outer) transaction
+    pub fn find_in_tx(&self, tx: &TxScope) -> error::Result<Vec<T>> {
+        let mut cursor = Cursor::new_in_tx(&tx.tx, self.helper.clone())?;
+        self.find_with_cursor(&mut cursor)
+    }
+
+    /// The flatbuffer data of the results, in its own read transaction
+    fn find_data(&self) -> error::Result<Vec<Vec<u8>>> {
+        unsafe {
+            let bytes_array = obx_query_find(self.obx_query);
+            let bytes_array = c::get_result_from_ptr(bytes_array, bytes_array)?;
+            let array = &*bytes_array;
+            let mut vec = Vec::with_capacity(array.count);
+            if array.count > 0 {
+                for bytes in slice::from_raw_parts(array.bytes, array.count) {
+                    vec.push(slice::from_raw_parts(bytes.data as *const u8, bytes.size).to_vec());
+                }
+            }
+            obx_bytes_array_free(bytes_array);
+            Ok(vec)
+        }
+    }
+
+    fn find_with_cursor(&self, cursor: &mut Cursor<T>) -> error::Result<Vec<T>> {
+        let mut vec = Vec::new();
+        let ids = self.find_ids_with_cursor(cursor)?;
 
         for id in ids {
             vec.push(
@@ -203,18 +276,46 @@ impl<T: OBBlanket> Query<T> {
 
     // TODO write test
     pub fn find_ids(&self) -> error::Result<Vec<c::obx_id>> {
-        let mut vec = Vec::new();
-        unsafe {
-            let cursor = Cursor::new(false, self.obx_store, self.helper.clone())?;
-            let c_id_array = self.cursor_find_ids(&mut *cursor.obx_cursor);
-            // TODO error check tx, cursor, with get_result_from_ptr
-            let c = &*c_id_array;
-            let len = c.count;
-            let ptr = c.ids;
-            let sl = slice::from_raw_parts(ptr, len);
-            vec.extend(sl);
-            get_result_from_ptr(ptr, vec)
+        let cursor = Cursor::new(false, self.obx_store, self.helper.clone())?;
+        self.find_ids_with_cursor(&cursor)
+    }
+
+    fn find_ids_with_cursor(&self, cursor: &Cursor<T>) -> error::Result<Vec<c::obx_id>> {
+        c::get_ids_from_array(unsafe { self.cursor_find_ids(&mut *cursor.obx_cursor) })
+    }
+
+    /// Calls f with a view of each matching object, until it returns false.
+    /// The views read the fields straight from the stored data, without copying them,
+    /// they are valid within the call, in a read transaction that lasts for the whole visit.
+    pub fn for_each_ref<F>(&self, f: F) -> error::Result<()>
+    where
+        T: RefExt,
+        F: FnMut(T::Ref<'_>) -> bool,
+    {
+        let cursor = Cursor::new(false, self.obx_store, self.helper.clone())?;
+        let mut visitor = RefVisitor { f, panic: None };
+        let code = unsafe {
+            obx_query_cursor_visit(
+                self.obx_query,
+                cursor.obx_cursor,
+                Some(visit_ref::<T, F>),
+                &mut visitor as *mut RefVisitor<F> as *mut std::os::raw::c_void,
+            )
+        };
+        if let Some(payload) = visitor.panic {
+            panic::resume_unwind(payload);
         }
+        c::call(code)
+    }
+
+    /// Iterates over the matching objects, reading one object at a time
+    /// in a read transaction held by the iterator.
+    /// Only the ids of the matches are collected up front, in one pass,
+    /// with the offset and limit of this query applied.
+    pub fn iter(&self) -> error::Result<Iter<'_, T>> {
+        let cursor = Cursor::new(false, self.obx_store, self.helper.clone())?;
+        let ids = self.find_ids_with_cursor(&cursor)?;
+        Ok(Iter::from_ids(cursor, ids))
     }
 
     fn cursor_count(&self, cursor: &mut OBX_cursor, out_count: *mut u64) -> error::Result<u64> {
@@ -243,226 +344,111 @@ impl<T: OBBlanket> Query<T> {
     }
 
     pub fn remove(&self) -> error::Result<u64> {
+        let mut cursor = Cursor::new(true, self.obx_store, self.helper.clone())?;
+        let count = self.remove_with_cursor(&mut cursor)?;
+        cursor.commit()?;
+        Ok(count)
+    }
+
+    /// Same as remove, but the removal is only committed with the given (outer) transaction
+    pub fn remove_in_tx(&self, tx: &TxScope) -> error::Result<u64> {
+        let mut cursor = Cursor::new_in_tx(&tx.tx, self.helper.clone())?;
+        self.remove_with_cursor(&mut cursor)
+    }
+
+    fn remove_with_cursor(&self, cursor: &mut Cursor<T>) -> error::Result<u64> {
         unsafe {
-            let mut cursor = Cursor::new(true, self.obx_store, self.helper.clone())?;
             let count: *mut u64 = &mut 0;
-            let err_code = self.cursor_remove(&mut *cursor.obx_cursor, count)?;
-            if err_code == 0 {
-                cursor.get_tx().success()?;
-            }
+            self.cursor_remove(&mut *cursor.obx_cursor, count)?;
             Ok(*count)
         }
     }
     // end cursor
 
-    // TODO implement later
-    // start aliasing
-    /*
-    pub(crate) unsafe fn param_string(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        value: PtrConstChar,
-    ) -> obx_err {
-        obx_query_param_string(self.obx_query, entity_id, property_id, value)
-    }
-
-    // For strings
-    pub(crate) unsafe fn param_2strings(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        value: PtrConstChar,
-        value2: PtrConstChar,
-    ) -> obx_err {
-        obx_query_param_2strings(self.obx_query, entity_id, property_id, value, value2)
-    }
-
-    pub(crate) unsafe fn param_strings(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        values: *const PtrConstChar, // ptr ptr === array of CString
-        count: usize,
-    ) -> obx_err {
-        obx_query_param_strings(self.obx_query, entity_id, property_id, values, count)
-    }
-
-    // For ints
-    pub(crate) unsafe fn param_int(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        value: i64,
-    ) -> obx_err {
-        obx_query_param_int(self.obx_query, entity_id, property_id, value)
-    }
-
-    pub(crate) unsafe fn param_2ints(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        value_a: i64,
-        value_b: i64,
-    ) -> obx_err {
-        obx_query_param_2ints(
-            self.obx_query,
-            entity_id,
-            property_id,
-            value_a,
-            value_b,
-        )
-    }
-
-    pub(crate) unsafe fn param_int64s(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        values: *const i64,
-        count: usize,
-    ) -> obx_err {
-        obx_query_param_int64s(self.obx_query, entity_id, property_id, values, count)
-    }
-
-    pub(crate) unsafe fn param_int32s(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        values: *const i32,
-        count: usize,
-    ) -> obx_err {
-        obx_query_param_int32s(self.obx_query, entity_id, property_id, values, count)
-    }
-
-    // For doubles
-    pub(crate) unsafe fn param_double(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        value: f64,
-    ) -> obx_err {
-        obx_query_param_double(self.obx_query, entity_id, property_id, value)
-    }
-
-    pub(crate) unsafe fn param_2doubles(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        value_a: f64,
-        value_b: f64,
-    ) -> obx_err {
-        obx_query_param_2doubles(
-            self.obx_query,
-            entity_id,
-            property_id,
-            value_a,
-            value_b,
-        )
-    }
-
-    // For bytes
-    pub(crate) unsafe fn param_bytes(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-        value: ConstVoidPtr,
-        size: usize,
-    ) -> obx_err {
-        obx_query_param_bytes(self.obx_query, entity_id, property_id, value, size)
-    }
-
-    pub(crate) unsafe fn param_get_type_size(
-        &mut self,
-        entity_id: obx_schema_id,
-        property_id: obx_schema_id,
-    ) -> usize {
-        obx_query_param_get_type_size(self.obx_query, entity_id, property_id)
-    }
-
-    // For aliases
-    pub(crate) unsafe fn param_alias_string(
-        &mut self,
-        alias: PtrConstChar,
-        value: PtrConstChar,
-    ) -> obx_err {
-        obx_query_param_alias_string(self.obx_query, alias, value)
-    }
-
-    pub(crate) unsafe fn param_alias_strings(
-        &mut self,
-        alias: PtrConstChar,
-        values: *const PtrConstChar,
-        count: usize,
-    ) -> obx_err {
-        obx_query_param_alias_strings(self.obx_query, alias, values, count)
-    }
-
-    pub(crate) unsafe fn param_alias_int(
-        &mut self,
-        alias: PtrConstChar,
-        value: i64,
-    ) -> obx_err {
-        obx_query_param_alias_int(self.obx_query, alias, value)
-    }
-
-    pub(crate) unsafe fn param_alias_2ints(
-        &mut self,
-        alias: PtrConstChar,
-        value_a: i64,
-        value_b: i64,
-    ) -> obx_err {
-        obx_query_param_alias_2ints(self.obx_query, alias, value_a, value_b)
-    }
-
-    pub(crate) unsafe fn param_alias_int64s(
-        &mut self,
-        alias: PtrConstChar,
-        values: *const i64,
-        count: usize,
-    ) -> obx_err {
-        obx_query_param_alias_int64s(self.obx_query, alias, values, count)
-    }
-
-    pub(crate) unsafe fn param_alias_int32s(
-        &mut self,
-        alias: PtrConstChar,
-        values: *const i32,
-        count: usize,
-    ) -> obx_err {
-        obx_query_param_alias_int32s(self.obx_query, alias, values, count)
-    }
-
-    pub(crate) unsafe fn param_alias_double(
-        &mut self,
-        alias: PtrConstChar,
-        value: f64,
-    ) -> obx_err {
-        obx_query_param_alias_double(self.obx_query, alias, value)
-    }
-
-    pub(crate) unsafe fn param_alias_2doubles(
-        &mut self,
-        alias: PtrConstChar,
-        value_a: f64,
-        value_b: f64,
-    ) -> obx_err {
-        obx_query_param_alias_2doubles(self.obx_query, alias, value_a, value_b)
-    }
-
-    pub(crate) unsafe fn param_alias_bytes(
-        &mut self,
-        alias: PtrConstChar,
-        value: ConstVoidPtr,
-        size: usize,
-    ) -> obx_err {
-        obx_query_param_alias_bytes(self.obx_query, alias, value, size)
-    }
-
-    pub(crate) unsafe fn param_alias_get_type_size(
-        &mut self,
-        alias: PtrConstChar,
-    ) -> usize {
-        obx_query_param_alias_get_type_size(self.obx_query, alias)
+    /// Replace the value of the condition on the given property,
+    /// the property must only have a single condition, otherwise use an alias.
+    /// The value type is checked against the generated condition factory field.
+    pub fn set_param<P, V>(&self, property: &impl AsRef<P>, value: V) -> error::Result<&Self>
+    where
+        P: ?Sized + BasicExt<T> + ParamProperty<T, V>,
+        V: ParamValue,
+    {
+        let (entity_id, property_id, _) = *property.as_ref().get_property_attrs();
+        let target = ParamTarget::Property(entity_id, property_id);
+        unsafe { value.set_param(self.obx_query, &target) }.map(|_| self)
+    }
+
+    /// Replace the value of the condition named with Condition::alias
+    pub fn set_alias<V: ParamValue>(&self, alias: &str, value: V) -> error::Result<&Self> {
+        unsafe { value.set_param(self.obx_query, &ParamTarget::Alias(alias)) }.map(|_| self)
+    }
+}
+
+// Owns a clone of the query, only the (thread-safe) factory is shared with the original,
+// so it can be moved to the thread of an observer, or of a worker.
+pub(crate) struct SendQuery<T: OBBlanket>(Query<T>);
+
+unsafe impl<T: OBBlanket + Send> Send for SendQuery<T> {}
+
+impl<T: OBBlanket> SendQuery<T> {
+    pub(crate) fn get(&self) -> &Query<T> {
+        &self.0
+    }
+}
+
+impl<T: 'static + OBBlanket + Send> Query<T> {
+    /// Passes the result of find to the callback, right away and after each
+    /// committed write to T, until the returned Observer is dropped.
+    /// The callback runs on a thread of the store, later parameter changes are not picked up.
+    pub fn subscribe(
+        &self,
+        callback: impl FnMut(Vec<T>) + Send + 'static,
+    ) -> error::Result<Observer<'_>> {
+        self.subscribe_with(false, callback)
+    }
+
+    /// Like subscribe, but the callback is skipped while the result stays the same
+    pub fn subscribe_distinct(
+        &self,
+        callback: impl FnMut(Vec<T>) + Send + 'static,
+    ) -> error::Result<Observer<'_>> {
+        self.subscribe_with(true, callback)
+    }
+
+    /// A clone of the query, with the current parameters, for another thread
+    pub(crate) fn send_clone(&self) -> error::Result<SendQuery<T>> {
+        self.clone().map(SendQuery)
+    }
+
+    fn subscribe_with(
+        &self,
+        skip_unchanged: bool,
+        mut callback: impl FnMut(Vec<T>) + Send + 'static,
+    ) -> error::Result<Observer<'_>> {
+        let schema_id = self.helper.get_entity_id();
+        let query = self.send_clone()?;
+
+        let mut last: Option<Vec<Vec<u8>>> = None;
+        let run = move |_: &[obx_schema_id]| match query.get().find_data() {
+            Ok(data) => {
+                if skip_unchanged && last.as_ref() == Some(&data) {
+                    return;
+                }
+                let helper = query.get().helper.as_ref();
+                let objects = data
+                    .iter()
+                    .map(|d| unsafe { object_from_data(helper, d) })
+                    .collect();
+                if skip_unchanged {
+                    last = Some(data);
+                }
+                callback(objects);
+            }
+            Err(err) => eprintln!("Error: query subscription: {err}"),
+        };
+        // registered first, so no write between the initial run and the observer is missed
+        let observer = Observer::new(self.obx_store, Some(schema_id), Box::new(run))?;
+        observer.trigger();
+        Ok(observer)
     }
-    */
 }
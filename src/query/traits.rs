@@ -1,7 +1,12 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
-use crate::{c, traits::OBBlanket};
+use crate::{
+    c,
+    time::TimeValue,
+    traits::{OBBlanket, PropertyConverter},
+};
 use core::marker::PhantomData;
+use flatbuffers::{FlatBufferBuilder, ForwardsUOffset, Table, UnionWIPOffset, VOffsetT, WIPOffset};
 use std::rc::Rc;
 
 use super::{
@@ -34,13 +39,11 @@ pub struct ConditionBuilder<Entity: OBBlanket> {
     ids_and_type: IdsAndType,
 }
 
-impl<Entity: OBBlanket> ConditionBuilder<Entity> {
-    fn get_property_attrs(&self) -> IdsAndType {
-        self.ids_and_type.clone()
-    }
-}
-
 pub trait BasicExt<Entity: OBBlanket> {
+    /// (entity_id, property_id, property_type) of the generated property,
+    /// also used to select a property on an existing query
+    fn get_property_attrs(&self) -> IdsAndType;
+
     fn order_flags(&mut self, of: i32) -> Condition<Entity>;
 
     // TODO test when there is support for Option<*> properties
@@ -50,6 +53,10 @@ pub trait BasicExt<Entity: OBBlanket> {
 }
 
 impl<Entity: OBBlanket> BasicExt<Entity> for ConditionBuilder<Entity> {
+    fn get_property_attrs(&self) -> IdsAndType {
+        self.ids_and_type.clone()
+    }
+
     fn order_flags(&mut self, of: i32) -> Condition<Entity> {
         Condition::new(self.get_property_attrs(), ConditionOp::OrderFlags(of))
     }
@@ -294,11 +301,223 @@ impl<Entity: OBBlanket> InOutExt<Entity, String> for ConditionBuilder<Entity> {
     }
 }
 
-/// Blankets
-pub trait BoolBlanket<Entity: OBBlanket>: BasicExt<Entity> // + EqExt<Entity, i64>
-{
+/// Conditions on Date and DateNano properties, in the unit of the property,
+/// with any TimeValue, e.g. a DateTime<Utc>, NaiveDateTime or SystemTime
+pub trait DateExt<Entity: OBBlanket> {
+    fn before(&self, t: &dyn TimeValue) -> Condition<Entity>;
+    fn after(&self, t: &dyn TimeValue) -> Condition<Entity>;
+    /// Inclusive
+    fn between(&self, from: &dyn TimeValue, to: &dyn TimeValue) -> Condition<Entity>;
 }
 
+impl<Entity: OBBlanket> ConditionBuilder<Entity> {
+    fn time_value(&self, t: &dyn TimeValue) -> i64 {
+        if self.ids_and_type.2 == c::OBXPropertyType_DateNano {
+            t.to_nanos()
+        } else {
+            t.to_millis()
+        }
+    }
+}
+
+impl<Entity: OBBlanket> DateExt<Entity> for ConditionBuilder<Entity> {
+    fn before(&self, t: &dyn TimeValue) -> Condition<Entity> {
+        Condition::new(
+            self.get_property_attrs(),
+            ConditionOp::Lt_i64(self.time_value(t)),
+        )
+    }
+    fn after(&self, t: &dyn TimeValue) -> Condition<Entity> {
+        Condition::new(
+            self.get_property_attrs(),
+            ConditionOp::Gt_i64(self.time_value(t)),
+        )
+    }
+    fn between(&self, from: &dyn TimeValue, to: &dyn TimeValue) -> Condition<Entity> {
+        Condition::new(
+            self.get_property_attrs(),
+            ConditionOp::Between_i64(self.time_value(from), self.time_value(to)),
+        )
+    }
+}
+
+/// A stored value, ready to be pushed into the table of the object
+pub enum StoredSlot {
+    Long(i64),
+    /// A string or bytes, created before the table was started
+    Offset(WIPOffset<UnionWIPOffset>),
+}
+
+impl StoredSlot {
+    pub fn push(self, builder: &mut FlatBufferBuilder, slot: VOffsetT) {
+        match self {
+            StoredSlot::Long(v) => builder.push_slot_always::<i64>(slot, v),
+            StoredSlot::Offset(o) => builder.push_slot_always(slot, o),
+        }
+    }
+}
+
+/// The stored types of properties with a PropertyConverter,
+/// the generated code takes the property type and the slot from it
+pub trait StoredValue: Sized {
+    const PROPERTY_TYPE: c::OBXPropertyType;
+
+    /// Strings and bytes are created here, before the table is started
+    fn create(&self, builder: &mut FlatBufferBuilder) -> StoredSlot;
+
+    /// # Safety
+    /// The slot of the table has to hold a value of this type
+    unsafe fn read(table: &Table, slot: VOffsetT) -> Option<Self>;
+
+    fn eq_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity>;
+    fn ne_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity>;
+    fn in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity>;
+    fn not_in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity>;
+}
+
+// for the stored types without a native (not) in condition
+fn group_condition<Entity: OBBlanket>(
+    cb: &ConditionBuilder<Entity>,
+    op: ConditionOp,
+    group: Vec<Condition<Entity>>,
+) -> Condition<Entity> {
+    Condition::new_group(cb.get_property_attrs(), op, group)
+}
+
+impl StoredValue for i64 {
+    const PROPERTY_TYPE: c::OBXPropertyType = c::OBXPropertyType_Long;
+
+    fn create(&self, _builder: &mut FlatBufferBuilder) -> StoredSlot {
+        StoredSlot::Long(*self)
+    }
+    unsafe fn read(table: &Table, slot: VOffsetT) -> Option<Self> {
+        table.get::<i64>(slot, None)
+    }
+    fn eq_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity> {
+        EqExt::eq(cb, self)
+    }
+    fn ne_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity> {
+        EqExt::ne(cb, self)
+    }
+    fn in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity> {
+        InOutExt::member_of(cb, values)
+    }
+    fn not_in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity> {
+        InOutExt::not_member_of(cb, values)
+    }
+}
+
+impl StoredValue for String {
+    const PROPERTY_TYPE: c::OBXPropertyType = c::OBXPropertyType_String;
+
+    fn create(&self, builder: &mut FlatBufferBuilder) -> StoredSlot {
+        StoredSlot::Offset(builder.create_string(self).as_union_value())
+    }
+    unsafe fn read(table: &Table, slot: VOffsetT) -> Option<Self> {
+        table
+            .get::<ForwardsUOffset<&str>>(slot, None)
+            .map(String::from)
+    }
+    fn eq_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity> {
+        EqExt::eq(cb, self)
+    }
+    fn ne_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity> {
+        EqExt::ne(cb, self)
+    }
+    fn in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity> {
+        InOutExt::member_of(cb, values)
+    }
+    fn not_in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity> {
+        let group = values.into_iter().map(|v| EqExt::ne(cb, v)).collect();
+        group_condition(cb, ConditionOp::All, group)
+    }
+}
+
+impl StoredValue for Vec<u8> {
+    const PROPERTY_TYPE: c::OBXPropertyType = c::OBXPropertyType_ByteVector;
+
+    fn create(&self, builder: &mut FlatBufferBuilder) -> StoredSlot {
+        StoredSlot::Offset(builder.create_vector(self).as_union_value())
+    }
+    unsafe fn read(table: &Table, slot: VOffsetT) -> Option<Self> {
+        table
+            .get::<ForwardsUOffset<flatbuffers::Vector<u8>>>(slot, None)
+            .map(|bytes| bytes.bytes().to_vec())
+    }
+    fn eq_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity> {
+        EqExt::eq(cb, self)
+    }
+    fn ne_condition<Entity: OBBlanket>(self, cb: &ConditionBuilder<Entity>) -> Condition<Entity> {
+        OrdExt::lt(cb, self.clone()).or(OrdExt::gt(cb, self))
+    }
+    fn in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity> {
+        let group = values.into_iter().map(|v| EqExt::eq(cb, v)).collect();
+        group_condition(cb, ConditionOp::Any, group)
+    }
+    fn not_in_condition<Entity: OBBlanket>(
+        values: Vec<Self>,
+        cb: &ConditionBuilder<Entity>,
+    ) -> Condition<Entity> {
+        let group = values.into_iter().map(|v| v.ne_condition(cb)).collect();
+        group_condition(cb, ConditionOp::All, group)
+    }
+}
+
+/// Conditions on properties with a PropertyConverter, on values of the field type.
+/// member_of of no values matches no object, not_member_of of no values every object.
+pub trait ConvertedExt<Entity: OBBlanket, C: PropertyConverter> {
+    fn eq(&self, value: C::Value) -> Condition<Entity>;
+    fn ne(&self, value: C::Value) -> Condition<Entity>;
+    fn member_of(&self, values: Vec<C::Value>) -> Condition<Entity>;
+    fn not_member_of(&self, values: Vec<C::Value>) -> Condition<Entity>;
+}
+
+impl<Entity: OBBlanket, C: PropertyConverter> ConvertedExt<Entity, C> for ConditionBuilder<Entity> {
+    fn eq(&self, value: C::Value) -> Condition<Entity> {
+        C::to_stored(&value).eq_condition(self)
+    }
+    fn ne(&self, value: C::Value) -> Condition<Entity> {
+        C::to_stored(&value).ne_condition(self)
+    }
+    fn member_of(&self, values: Vec<C::Value>) -> Condition<Entity> {
+        // an empty group would match every object
+        if values.is_empty() {
+            return self.is_null().and(self.is_not_null());
+        }
+        C::Stored::in_condition(values.iter().map(C::to_stored).collect(), self)
+    }
+    fn not_member_of(&self, values: Vec<C::Value>) -> Condition<Entity> {
+        if values.is_empty() {
+            return self.is_null().or(self.is_not_null());
+        }
+        C::Stored::not_in_condition(values.iter().map(C::to_stored).collect(), self)
+    }
+}
+
+/// Blankets
+pub trait BoolBlanket<Entity: OBBlanket>: BasicExt<Entity> {}
+
 pub trait CharBlanket<Entity: OBBlanket>:
     BasicExt<Entity>
     + EqExt<Entity, i64>
@@ -386,6 +605,21 @@ pub trait U64Blanket<Entity: OBBlanket>:
 {
 }
 
+/// Without BetweenExt, DateExt::between takes the dates
+pub trait DateBlanket<Entity: OBBlanket>:
+    BasicExt<Entity>
+    + DateExt<Entity>
+    + EqExt<Entity, i64>
+    + OrdExt<Entity, i64>
+    + InOutExt<Entity, i64>
+{
+}
+
+pub trait ConvertedBlanket<Entity: OBBlanket, C: PropertyConverter>:
+    BasicExt<Entity> + ConvertedExt<Entity, C>
+{
+}
+
 pub trait VecU8Blanket<Entity: OBBlanket>:
     BasicExt<Entity> + EqExt<Entity, Vec<u8>> + OrdExt<Entity, Vec<u8>>
 {
@@ -484,6 +718,18 @@ impl<Entity: OBBlanket> U64Blanket<Entity> for Entity where
         + InOutExt<Entity, i64>
 {
 }
+impl<Entity: OBBlanket> DateBlanket<Entity> for Entity where
+    Entity: BasicExt<Entity>
+        + DateExt<Entity>
+        + EqExt<Entity, i64>
+        + OrdExt<Entity, i64>
+        + InOutExt<Entity, i64>
+{
+}
+impl<Entity: OBBlanket, C: PropertyConverter> ConvertedBlanket<Entity, C> for Entity where
+    Entity: BasicExt<Entity> + ConvertedExt<Entity, C>
+{
+}
 impl<Entity: OBBlanket> VecU8Blanket<Entity> for Entity where
     Entity: BasicExt<Entity> + EqExt<Entity, Vec<u8>> + OrdExt<Entity, Vec<u8>>
 {
@@ -524,6 +770,8 @@ mod tests {
         fn flatten(&self, builder: &mut flatbuffers::FlatBufferBuilder) {}
     }
 
+    impl traits::RelationExt for TEntity {}
+
     impl traits::IdExt for TEntity2 {
         fn get_id(&self) -> c::obx_id {
             2
@@ -535,6 +783,8 @@ mod tests {
         fn flatten(&self, builder: &mut flatbuffers::FlatBufferBuilder) {}
     }
 
+    impl traits::RelationExt for TEntity2 {}
+
     // conflicts with original generic one
     // impl traits::OBBlanket for TEntity2 {}
 
@@ -0,0 +1,345 @@
+use std::cell::OnceCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::c::{self, obx_id, obx_schema_id, OBX_box};
+use crate::error;
+use crate::store::Store;
+use crate::traits::{IdExt, OBBlanket};
+use crate::txn::TxOutcome;
+
+/// A to-one relation field, stored as the id of the target object.
+/// The target itself is only read from its Box on the first access.
+pub struct ToOne<Target> {
+    target_id: obx_id,
+    target: OnceCell<Option<Target>>,
+}
+
+impl<Target> Default for ToOne<Target> {
+    fn default() -> Self {
+        ToOne::new(0)
+    }
+}
+
+impl<Target: fmt::Debug> fmt::Debug for ToOne<Target> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToOne")
+            .field("target_id", &self.target_id)
+            .field("target", &self.target.get())
+            .finish()
+    }
+}
+
+impl<Target> ToOne<Target> {
+    pub fn new(target_id: obx_id) -> Self {
+        ToOne {
+            target_id,
+            target: OnceCell::new(),
+        }
+    }
+
+    /// 0 means there is no target
+    pub fn target_id(&self) -> obx_id {
+        self.target_id
+    }
+
+    pub fn set_target_id(&mut self, target_id: obx_id) {
+        if self.target_id != target_id {
+            self.target_id = target_id;
+            self.target = OnceCell::new();
+        }
+    }
+}
+
+impl<Target: 'static + OBBlanket> ToOne<Target> {
+    /// Reads the target from its Box on the first call, then keeps it.
+    /// None if there is no target, or if it was removed.
+    pub fn target(&self, store: &Store) -> error::Result<Option<&Target>> {
+        if let Some(target) = self.target.get() {
+            return Ok(target.as_ref());
+        }
+        let target = if self.target_id == 0 {
+            None
+        } else {
+            store.get_box::<Target>()?.get(self.target_id)?
+        };
+        Ok(self.target.get_or_init(|| target).as_ref())
+    }
+
+    /// The target has to be put before, otherwise its id is 0
+    /// and the relation is cleared. None clears the relation.
+    pub fn set_target(&mut self, target: Option<&Target>) {
+        self.set_target_id(target.map_or(0, |t| t.get_id()));
+    }
+}
+
+// the native box of the source entity, owned by the store
+fn source_box(store: &Store, source_entity_id: obx_schema_id) -> error::Result<*mut OBX_box> {
+    c::new_mut(unsafe { c::obx_box(store.obx_store, source_entity_id) })
+}
+
+/// Pending changes of a standalone relation, applied by the Cursor on put.
+pub trait ToManyExt {
+    /// (added, removed) target ids, that were not committed yet
+    fn pending(&self) -> (Vec<obx_id>, Vec<obx_id>);
+
+    /// Binds to the stored source object, the pending changes are cleared
+    fn attach(
+        &mut self,
+        source_entity_id: obx_schema_id,
+        relation_id: obx_schema_id,
+        source_id: obx_id,
+    );
+
+    /// Binds to the source object, that was put with the pending changes.
+    /// They are kept until the transaction is committed, to be put again
+    /// if it's aborted.
+    fn applied(
+        &mut self,
+        source_entity_id: obx_schema_id,
+        relation_id: obx_schema_id,
+        source_id: obx_id,
+        outcome: TxOutcome,
+    );
+}
+
+/// A standalone to-many relation field, stored apart from the object.
+/// Changes are kept until the owning object is put.
+pub struct ToMany<Target> {
+    // (source entity id, relation id, source id), once the owner is read or put
+    source: Option<(obx_schema_id, obx_schema_id, obx_id)>,
+    added: Vec<obx_id>,
+    removed: Vec<obx_id>,
+    // (added, removed) of the last put, until its transaction is committed
+    in_flight: Option<(Vec<obx_id>, Vec<obx_id>, TxOutcome)>,
+    phantom_data: PhantomData<Target>,
+}
+
+impl<Target> Default for ToMany<Target> {
+    fn default() -> Self {
+        ToMany {
+            source: None,
+            added: Vec::new(),
+            removed: Vec::new(),
+            in_flight: None,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<Target> fmt::Debug for ToMany<Target> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToMany")
+            .field("source", &self.source)
+            .field("added", &self.added)
+            .field("removed", &self.removed)
+            .field(
+                "in_flight",
+                &self.in_flight.as_ref().map(|(a, r, _)| (a, r)),
+            )
+            .finish()
+    }
+}
+
+impl<Target> ToManyExt for ToMany<Target> {
+    fn pending(&self) -> (Vec<obx_id>, Vec<obx_id>) {
+        let (mut added, mut removed) = match &self.in_flight {
+            Some((added, removed, outcome)) if !outcome.is_committed() => {
+                (added.clone(), removed.clone())
+            }
+            _ => (Vec::new(), Vec::new()),
+        };
+        for id in self.removed.iter() {
+            added.retain(|a| a != id);
+            if !removed.contains(id) {
+                removed.push(*id);
+            }
+        }
+        for id in self.added.iter() {
+            removed.retain(|r| r != id);
+            if !added.contains(id) {
+                added.push(*id);
+            }
+        }
+        (added, removed)
+    }
+
+    fn attach(
+        &mut self,
+        source_entity_id: obx_schema_id,
+        relation_id: obx_schema_id,
+        source_id: obx_id,
+    ) {
+        self.source = Some((source_entity_id, relation_id, source_id));
+        self.added.clear();
+        self.removed.clear();
+        self.in_flight = None;
+    }
+
+    fn applied(
+        &mut self,
+        source_entity_id: obx_schema_id,
+        relation_id: obx_schema_id,
+        source_id: obx_id,
+        outcome: TxOutcome,
+    ) {
+        let (added, removed) = self.pending();
+        self.source = Some((source_entity_id, relation_id, source_id));
+        self.added.clear();
+        self.removed.clear();
+        self.in_flight = Some((added, removed, outcome));
+    }
+}
+
+impl<Target> ToMany<Target> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The target has to be put before, ids of 0 are ignored
+    pub fn add_id(&mut self, target_id: obx_id) {
+        if target_id == 0 {
+            return;
+        }
+        self.removed.retain(|id| *id != target_id);
+        if !self.added.contains(&target_id) {
+            self.added.push(target_id);
+        }
+    }
+
+    pub fn remove_id(&mut self, target_id: obx_id) {
+        if target_id == 0 {
+            return;
+        }
+        self.added.retain(|id| *id != target_id);
+        if !self.removed.contains(&target_id) {
+            self.removed.push(target_id);
+        }
+    }
+
+    /// Whether there are changes, that were not put or not committed yet
+    pub fn has_pending_changes(&self) -> bool {
+        let (added, removed) = self.pending();
+        !added.is_empty() || !removed.is_empty()
+    }
+
+    /// The stored target ids with the pending changes applied
+    pub fn target_ids(&self, store: &Store) -> error::Result<Vec<obx_id>> {
+        let mut ids = match self.source {
+            Some((entity_id, relation_id, source_id)) if source_id != 0 => {
+                let obx_box = source_box(store, entity_id)?;
+                c::get_ids_from_array(unsafe {
+                    c::obx_box_rel_get_ids(obx_box, relation_id, source_id)
+                })?
+            }
+            _ => Vec::new(),
+        };
+        let (added, removed) = self.pending();
+        ids.retain(|id| !removed.contains(id));
+        for id in added {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+}
+
+impl<Target: IdExt> ToMany<Target> {
+    pub fn add(&mut self, target: &Target) {
+        self.add_id(target.get_id());
+    }
+
+    pub fn remove(&mut self, target: &Target) {
+        self.remove_id(target.get_id());
+    }
+}
+
+impl<Target: 'static + OBBlanket> ToMany<Target> {
+    /// Reads the targets from their Box, removed targets are skipped
+    pub fn targets(&self, store: &Store) -> error::Result<Vec<Target>> {
+        let ids = self.target_ids(store)?;
+        let targets = store.get_box::<Target>()?.get_many(&ids)?;
+        Ok(targets.into_iter().flatten().collect())
+    }
+}
+
+/// The relation of the source entity, that a backlink follows in reverse
+#[derive(Debug, Clone, Copy)]
+pub enum BacklinkSource {
+    /// The relation property of a ToOne
+    Property(obx_schema_id),
+    /// The standalone relation of a ToMany
+    Relation(obx_schema_id),
+}
+
+pub trait BacklinksExt {
+    /// Binds to the stored target object
+    fn attach(&mut self, source_entity_id: obx_schema_id, via: BacklinkSource, target_id: obx_id);
+}
+
+/// The source objects, that relate to this object through a ToOne or a ToMany.
+/// Backlinks are not stored, they are looked up on every access.
+pub struct Backlinks<Source> {
+    // (source entity id, relation, target id), once the owner is read or put
+    target: Option<(obx_schema_id, BacklinkSource, obx_id)>,
+    phantom_data: PhantomData<Source>,
+}
+
+impl<Source> Default for Backlinks<Source> {
+    fn default() -> Self {
+        Backlinks {
+            target: None,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<Source> fmt::Debug for Backlinks<Source> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Backlinks")
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl<Source> BacklinksExt for Backlinks<Source> {
+    fn attach(&mut self, source_entity_id: obx_schema_id, via: BacklinkSource, target_id: obx_id) {
+        self.target = Some((source_entity_id, via, target_id));
+    }
+}
+
+impl<Source> Backlinks<Source> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empty, as long as the owner was neither read nor put
+    pub fn source_ids(&self, store: &Store) -> error::Result<Vec<obx_id>> {
+        match self.target {
+            Some((entity_id, via, target_id)) if target_id != 0 => {
+                let obx_box = source_box(store, entity_id)?;
+                c::get_ids_from_array(unsafe {
+                    match via {
+                        BacklinkSource::Property(property_id) => {
+                            c::obx_box_get_backlink_ids(obx_box, property_id, target_id)
+                        }
+                        BacklinkSource::Relation(relation_id) => {
+                            c::obx_box_rel_get_backlink_ids(obx_box, relation_id, target_id)
+                        }
+                    }
+                })
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+impl<Source: 'static + OBBlanket> Backlinks<Source> {
+    /// Reads the source objects from their Box
+    pub fn sources(&self, store: &Store) -> error::Result<Vec<Source>> {
+        let ids = self.source_ids(store)?;
+        let sources = store.get_box::<Source>()?.get_many(&ids)?;
+        Ok(sources.into_iter().flatten().collect())
+    }
+}
@@ -1,34 +1,55 @@
 #![allow(dead_code)]
 use std::ffi::CString;
+use std::ops::Deref;
 use std::path::Path;
-use std::rc::Rc;
-
-use anymap::AnyMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::c::{self, *};
 use crate::error::{self, Error};
 
-use crate::opt::Opt;
-use crate::traits::{EntityFactoryExt, OBBlanket};
-use crate::util::ToCChar;
+use crate::observer::Observer;
+use crate::opt::{Opt, MEMORY_PREFIX};
+use crate::traits::{EntityFactoryExt, FactoryMap, OBBlanket};
+use crate::txn::{Tx, TxScope};
 
 // Caveat: copy and drop are mutually exclusive
 
 pub struct Store {
-    pub trait_map: AnyMap, // passed as a ref to a Box
+    pub trait_map: FactoryMap, // passed as a ref to a Box
     // TODO confirm: model and opt are cleaned up already and zero'ed, or else we'll have a double-free
     pub(crate) obx_store: *mut OBX_store, // TODO confirm: model and opt are cleaned up already
+    // opened elsewhere, only this instance is closed, the store stays open
+    attached: bool,
+    // unknown, if attached by id
+    directory: Option<String>,
+    // the files are removed after closing
+    temporary: bool,
 }
 
 impl Drop for Store {
     fn drop(&mut self) {
         if !self.obx_store.is_null() {
-            match self.prepare_then_close() {
+            let result = if self.attached {
+                self.close()
+            } else {
+                self.prepare_then_close()
+            };
+            match result {
                 Err(err) => eprintln!("Error: store: {err}"),
                 _ => (),
             }
             self.obx_store = std::ptr::null_mut();
         }
+        if let (true, Some(dir)) = (self.temporary, self.directory.take()) {
+            if let Err(err) = remove_db_files(&dir) {
+                eprintln!("Error: store: {err}");
+            }
+            if !dir.starts_with(MEMORY_PREFIX) {
+                let _ = std::fs::remove_dir(&dir);
+            }
+        }
     }
 }
 
@@ -36,19 +57,45 @@ impl Drop for Store {
 
 impl Store {
     /// Assumes ownership of map, and Opt,
-    pub fn new(mut opt: Opt, map: AnyMap) -> error::Result<Self> {
+    pub fn new(mut opt: Opt, map: FactoryMap) -> error::Result<Self> {
+        let directory = opt.get_directory().to_string();
         let obx_store = c::new_mut(unsafe { obx_store_open(opt.obx_opt) })?;
         // This prevents a double free
         opt.ptr_consumed = !obx_store.is_null();
         let r = Store {
             trait_map: map,
             obx_store,
+            attached: false,
+            directory: Some(directory),
+            temporary: false,
         };
         Ok(r)
     }
 
+    /// Opens a store in a new directory under the temp dir of the system,
+    /// the files are removed when it's dropped. Assumes ownership of map, and Opt,
+    pub fn temporary(opt: Opt, map: FactoryMap) -> error::Result<Self> {
+        let dir = std::env::temp_dir().join(unique_name());
+        match dir.to_str() {
+            Some(dir) => Self::new_temporary(opt, map, dir.to_string()),
+            None => Error::new_local("Error: unable to parse the temp dir").as_result(),
+        }
+    }
+
+    /// Like temporary, but the store only lives in memory
+    pub fn temporary_in_memory(opt: Opt, map: FactoryMap) -> error::Result<Self> {
+        Self::new_temporary(opt, map, format!("{MEMORY_PREFIX}{}", unique_name()))
+    }
+
+    fn new_temporary(opt: Opt, map: FactoryMap, dir: String) -> error::Result<Self> {
+        opt.directory(Path::new(&dir))?;
+        let mut store = Self::new(opt, map)?;
+        store.temporary = true;
+        Ok(store)
+    }
+
     pub fn get_box<T: 'static + OBBlanket>(&self) -> error::Result<crate::r#box::Box<T>> {
-        let helper = if let Some(h) = self.trait_map.get::<Rc<dyn EntityFactoryExt<T>>>() {
+        let helper = if let Some(h) = self.trait_map.get::<Arc<dyn EntityFactoryExt<T>>>() {
             h
         } else {
             Error::new_local("Error: unable to get entity helper").as_result()?
@@ -56,62 +103,188 @@ impl Store {
         Ok(crate::r#box::Box::<T>::new(self.obx_store, helper.clone()))
     }
 
+    /// Calls back after each committed write to T, on a thread of the store,
+    /// until the returned Observer is dropped.
+    pub fn observe<T: 'static + OBBlanket>(
+        &self,
+        mut callback: impl FnMut() + Send + 'static,
+    ) -> error::Result<Observer<'_>> {
+        let entity_id = self.get_box::<T>()?.helper.get_entity_id();
+        Observer::new(
+            self.obx_store,
+            Some(entity_id),
+            Box::new(move |_| callback()),
+        )
+    }
+
+    /// Like observe, with the entity id of T sent over a channel
+    pub fn observe_channel<T: 'static + OBBlanket>(
+        &self,
+    ) -> error::Result<(Observer<'_>, mpsc::Receiver<obx_schema_id>)> {
+        let entity_id = self.get_box::<T>()?.helper.get_entity_id();
+        Observer::new_channel(self.obx_store, Some(entity_id))
+    }
+
+    /// Calls back with the ids of the changed entity types, after each committed write
+    pub fn observe_all(
+        &self,
+        callback: impl FnMut(&[obx_schema_id]) + Send + 'static,
+    ) -> error::Result<Observer<'_>> {
+        Observer::new(self.obx_store, None, Box::new(callback))
+    }
+
+    /// Runs the closure in a single write transaction, spanning all boxes
+    /// taken from the TxScope. Commits on Ok, aborts on Err or on a panic.
+    pub fn write_tx<R>(&self, f: impl FnOnce(&TxScope) -> error::Result<R>) -> error::Result<R> {
+        let scope = TxScope::new(
+            Tx::new_mut(self.obx_store)?,
+            self.obx_store,
+            &self.trait_map,
+        );
+        let result = f(&scope);
+        scope.finish(result)
+    }
+
+    /// Runs the closure in a single read transaction, spanning all boxes
+    /// taken from the TxScope.
+    pub fn read_tx<R>(&self, f: impl FnOnce(&TxScope) -> error::Result<R>) -> error::Result<R> {
+        let scope = TxScope::new(Tx::new(self.obx_store)?, self.obx_store, &self.trait_map);
+        f(&scope)
+    }
+
     pub fn is_open(path: &Path) -> bool {
-        unsafe { obx_store_is_open(path.as_c_char_ptr()) }
+        match path_to_cstring(path) {
+            Ok(path) => unsafe { obx_store_is_open(path.as_ptr()) },
+            Err(_) => false,
+        }
     }
 
-    // TODO support later
-    /*
-    pub fn from_path_attach(path: &Path) -> Self {
-        Store {
-            obx_store: unsafe { obx_store_attach(path.to_c_char()) },
-            error: None,
-            trait_map: None,
+    /// The size of the data file in bytes, zero for in-memory stores
+    pub fn db_file_size(&self) -> error::Result<u64> {
+        let dir = str_to_cstring(self.get_directory()?)?;
+        Ok(unsafe { obx_db_file_size(dir.as_ptr()) } as u64)
+    }
+
+    /// Removes the database files in the directory, refuses if a store is open there
+    pub fn remove_files(dir: &Path) -> error::Result<()> {
+        if Self::is_open(dir) {
+            return Error::new_local("Error: the store in the directory is open").as_result();
+        }
+        match dir.to_str() {
+            Some(dir) => remove_db_files(dir),
+            None => Error::new_local("Error: unable to parse the directory").as_result(),
         }
     }
 
-    pub fn from_store_id_attach(store_id: u64) -> Self {
-        Store {
-            obx_store: unsafe { obx_store_attach_id(store_id) },
-            error: None,
-            trait_map: None,
+    /// Copies the data file into the given directory, which can be opened as a store.
+    /// The copy is made inside an exclusive write transaction, that isn't committed,
+    /// so it's consistent. Writers are blocked until it's done, don't call it from
+    /// inside a write transaction of the same thread.
+    pub fn backup_to(&self, dir: &Path) -> error::Result<()> {
+        let source = self.get_directory()?;
+        if source.starts_with(MEMORY_PREFIX) {
+            return Error::new_local("Error: an in-memory store has no files").as_result();
+        }
+        if Self::is_open(dir) {
+            return Error::new_local("Error: the store in the directory is open").as_result();
+        }
+        // no other write can be committed while the file is read
+        let tx = Tx::new_mut(self.obx_store)?;
+        let result = std::fs::create_dir_all(dir)
+            .and_then(|_| std::fs::copy(Path::new(source).join(DATA_FILE), dir.join(DATA_FILE)));
+        drop(tx);
+        result.map(|_| ()).or_else(|err| {
+            Error::new_local(&format!("Error: unable to copy the data file: {err}")).as_result()
+        })
+    }
+
+    fn get_directory(&self) -> error::Result<&str> {
+        match &self.directory {
+            Some(dir) => Ok(dir),
+            None => Error::new_local("Error: the directory of the store is unknown").as_result(),
         }
     }
 
+    /// Attaches to the store opened in this process, in the given directory,
+    /// the map can be a different one, e.g. from another crate.
+    /// Only this instance is closed when dropped, the store stays open for the others.
+    pub fn attach(path: &Path, map: FactoryMap) -> error::Result<Self> {
+        let c_path = path_to_cstring(path)?;
+        let mut store = Self::from_attached(unsafe { obx_store_attach(c_path.as_ptr()) }, map)?;
+        store.directory = path.to_str().map(String::from);
+        Ok(store)
+    }
+
+    /// Like attach, by the id of an open store, see Store::id
+    pub fn attach_id(store_id: u64, map: FactoryMap) -> error::Result<Self> {
+        Self::from_attached(unsafe { obx_store_attach_id(store_id) }, map)
+    }
+
+    /// Attaches to the store in the directory of the options, if it's open in this process,
+    /// otherwise it's opened. Returns true when attached.
+    /// Assumes ownership of map, and Opt,
     pub fn attach_or_open(
-        opt: *mut OBX_store_options,
+        mut opt: Opt,
+        map: FactoryMap,
         check_matching_options: bool,
-        out_attached: *mut bool,
-    ) -> Self {
-        Store {
-            obx_store: unsafe {
-                obx_store_attach_or_open(opt, check_matching_options, out_attached)
-            },
-            error: None,
-            trait_map: None,
+    ) -> error::Result<(Self, bool)> {
+        let directory = opt.get_directory().to_string();
+        let mut attached = false;
+        let obx_store = c::new_mut(unsafe {
+            obx_store_attach_or_open(opt.obx_opt, check_matching_options, &mut attached)
+        })?;
+        // This prevents a double free
+        opt.ptr_consumed = !obx_store.is_null();
+        let r = Store {
+            trait_map: map,
+            obx_store,
+            attached,
+            directory: Some(directory),
+            temporary: false,
+        };
+        Ok((r, attached))
+    }
+
+    fn from_attached(obx_store: *mut OBX_store, map: FactoryMap) -> error::Result<Self> {
+        if obx_store.is_null() {
+            return Error::new_local("Error: no open store to attach to").as_result();
         }
+        Ok(Store {
+            trait_map: map,
+            obx_store,
+            attached: true,
+            directory: None,
+            temporary: false,
+        })
+    }
+
+    /// A reference that doesn't keep the store open
+    pub fn weak(&self) -> error::Result<WeakStore> {
+        c::new_mut(unsafe { obx_weak_store(self.obx_store) })
+            .map(|obx_weak_store| WeakStore { obx_weak_store })
     }
-    */
 
     // TODO Determine if this is safe
     pub fn id(&self) -> u64 {
         unsafe { obx_store_id(self.obx_store) }
     }
 
-    // TODO impl without Copy/Clone trait, because Drop, then use over channels
-    // pub fn clone(&self) -> Self {
-    //   Store {
-    //     obx_store: unsafe { obx_store_clone(self.obx_store) },
-    //     error: None,
-    //   }
-    // }
+    /// Moves the store behind a SharedStore, to use it from several threads
+    pub fn into_shared(self) -> SharedStore {
+        SharedStore {
+            store: Arc::new(SyncStore(self)),
+        }
+    }
 
-    pub fn from_core_wrap(core_store: &mut Vec<u8>, map: AnyMap) -> error::Result<Self> {
+    pub fn from_core_wrap(core_store: &mut Vec<u8>, map: FactoryMap) -> error::Result<Self> {
         // TODO test
         let ptr = unsafe { obx_store_wrap(core_store.as_ptr() as *mut std::ffi::c_void) };
         c::new_mut(ptr).map(|s| Store {
             obx_store: s,
             trait_map: map,
+            attached: false,
+            directory: None,
+            temporary: false,
         })
     }
 
@@ -171,3 +344,100 @@ impl Store {
         self.prepare_to_close()?.close()
     }
 }
+
+// The file with the data, in the directory of a store
+const DATA_FILE: &str = "data.mdb";
+
+// Unique in the process, and unlikely to collide with the leftovers of other runs
+fn unique_name() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    format!(
+        "objectbox-{}-{}-{nanos}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+fn remove_db_files(dir: &str) -> error::Result<()> {
+    let dir = str_to_cstring(dir)?;
+    c::call(unsafe { obx_remove_db_files(dir.as_ptr()) })
+}
+
+fn str_to_cstring(s: &str) -> error::Result<CString> {
+    CString::new(s).or_else(|_| Error::new_local("Error: unable to parse the path").as_result())
+}
+
+fn path_to_cstring(path: &Path) -> error::Result<CString> {
+    match path.to_str().map(CString::new) {
+        Some(Ok(path)) => Ok(path),
+        _ => Error::new_local("Error: unable to parse the path").as_result(),
+    }
+}
+
+/// Refers to a store without keeping it open, e.g. for a plugin or a cache.
+/// Upgrading attaches to the store, as long as it is open.
+pub struct WeakStore {
+    obx_weak_store: *mut OBX_weak_store,
+}
+
+// The native weak store is thread-safe
+unsafe impl Send for WeakStore {}
+unsafe impl Sync for WeakStore {}
+
+impl Drop for WeakStore {
+    fn drop(&mut self) {
+        if !self.obx_weak_store.is_null() {
+            unsafe { obx_weak_store_free(self.obx_weak_store) };
+            self.obx_weak_store = std::ptr::null_mut();
+        }
+    }
+}
+
+impl WeakStore {
+    /// By the id of an open store, see Store::id
+    pub fn by_id(store_id: u64) -> error::Result<Self> {
+        c::new_mut(unsafe { obx_weak_store_by_id(store_id) })
+            .map(|obx_weak_store| WeakStore { obx_weak_store })
+    }
+
+    /// An attached Store, fails once the store is closed
+    pub fn upgrade(&self, map: FactoryMap) -> error::Result<Store> {
+        let obx_store = unsafe { obx_weak_store_lock(self.obx_weak_store) };
+        if obx_store.is_null() {
+            return Error::new_local("Error: the store is closed").as_result();
+        }
+        Store::from_attached(obx_store, map)
+    }
+}
+
+// The native store is thread-safe, and so are the factories of the map
+struct SyncStore(Store);
+
+unsafe impl Send for SyncStore {}
+unsafe impl Sync for SyncStore {}
+
+/// A Store that can be cloned, and sent to other threads,
+/// it is closed when the last clone is dropped.
+/// Boxes and transactions are taken per thread, through Deref.
+#[derive(Clone)]
+pub struct SharedStore {
+    store: Arc<SyncStore>,
+}
+
+impl SharedStore {
+    /// Assumes ownership of map, and Opt,
+    pub fn new(opt: Opt, map: FactoryMap) -> error::Result<Self> {
+        Store::new(opt, map).map(Store::into_shared)
+    }
+}
+
+impl Deref for SharedStore {
+    type Target = Store;
+
+    fn deref(&self) -> &Store {
+        &self.store.0
+    }
+}
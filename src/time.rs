@@ -0,0 +1,117 @@
+//! Values of Date and DateNano properties, stored as milliseconds,
+//! respectively nanoseconds, since the unix epoch, in UTC.
+use std::time::SystemTime;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Conversion of the field types of Date and DateNano properties.
+/// Values that don't fit are clamped, e.g. DateNano after the year 2262.
+/// Conditions take any of them as `&dyn TimeValue`.
+pub trait TimeValue {
+    fn to_millis(&self) -> i64;
+    fn from_millis(millis: i64) -> Self
+    where
+        Self: Sized;
+    fn to_nanos(&self) -> i64;
+    fn from_nanos(nanos: i64) -> Self
+    where
+        Self: Sized;
+}
+
+impl TimeValue for DateTime<Utc> {
+    fn to_millis(&self) -> i64 {
+        self.timestamp_millis()
+    }
+
+    fn from_millis(millis: i64) -> Self {
+        DateTime::from_timestamp_millis(millis).unwrap_or(if millis < 0 {
+            DateTime::<Utc>::MIN_UTC
+        } else {
+            DateTime::<Utc>::MAX_UTC
+        })
+    }
+
+    fn to_nanos(&self) -> i64 {
+        self.timestamp_nanos_opt()
+            .unwrap_or(if self.timestamp() < 0 {
+                i64::MIN
+            } else {
+                i64::MAX
+            })
+    }
+
+    fn from_nanos(nanos: i64) -> Self {
+        DateTime::from_timestamp_nanos(nanos)
+    }
+}
+
+impl TimeValue for NaiveDateTime {
+    fn to_millis(&self) -> i64 {
+        self.and_utc().to_millis()
+    }
+
+    fn from_millis(millis: i64) -> Self {
+        DateTime::<Utc>::from_millis(millis).naive_utc()
+    }
+
+    fn to_nanos(&self) -> i64 {
+        self.and_utc().to_nanos()
+    }
+
+    fn from_nanos(nanos: i64) -> Self {
+        DateTime::<Utc>::from_nanos(nanos).naive_utc()
+    }
+}
+
+impl TimeValue for SystemTime {
+    fn to_millis(&self) -> i64 {
+        DateTime::<Utc>::from(*self).to_millis()
+    }
+
+    fn from_millis(millis: i64) -> Self {
+        DateTime::<Utc>::from_millis(millis).into()
+    }
+
+    fn to_nanos(&self) -> i64 {
+        DateTime::<Utc>::from(*self).to_nanos()
+    }
+
+    fn from_nanos(nanos: i64) -> Self {
+        DateTime::<Utc>::from_nanos(nanos).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn round_trips() {
+        let dt = DateTime::from_timestamp_nanos(1_700_000_000_123_456_789);
+        assert_eq!(1_700_000_000_123, dt.to_millis());
+        assert_eq!(dt, DateTime::<Utc>::from_nanos(dt.to_nanos()));
+        assert_eq!(
+            DateTime::from_timestamp_millis(1_700_000_000_123).unwrap(),
+            DateTime::<Utc>::from_millis(dt.to_millis())
+        );
+
+        let naive = dt.naive_utc();
+        assert_eq!(naive, NaiveDateTime::from_nanos(naive.to_nanos()));
+
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(-1500, before_epoch.to_millis());
+        assert_eq!(before_epoch, SystemTime::from_millis(-1500));
+        assert_eq!(-1_500_000_000, before_epoch.to_nanos());
+    }
+
+    #[test]
+    fn clamped() {
+        assert_eq!(i64::MAX, DateTime::<Utc>::MAX_UTC.to_nanos());
+        assert_eq!(i64::MIN, DateTime::<Utc>::MIN_UTC.to_nanos());
+        assert_eq!(
+            DateTime::<Utc>::MAX_UTC,
+            DateTime::<Utc>::from_millis(i64::MAX)
+        );
+    }
+}
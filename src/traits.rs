@@ -2,7 +2,10 @@
 use std::marker::PhantomData;
 
 use crate::c;
-use flatbuffers::FlatBufferBuilder;
+use crate::query::condition::Condition;
+use crate::query::traits::StoredValue;
+use crate::relations::ToManyExt;
+use flatbuffers::{FlatBufferBuilder, Table};
 
 pub trait FBOBBridge {
     fn flatten(&self, builder: &mut FlatBufferBuilder);
@@ -16,33 +19,68 @@ pub trait IdExt {
     fn set_id(&mut self, id: c::obx_id);
 }
 
-// TODO
-/*
 pub trait RelationExt {
-  fn to_one_relation<T>(&self) -> T;
+    /// The standalone relations of the entity, by relation id,
+    /// their pending changes are put together with the object.
+    fn to_many_relations(&mut self) -> Vec<(c::obx_schema_id, &mut dyn ToManyExt)> {
+        Vec::new()
+    }
 
-  /// Any is rust's dynamic type? If so, then the relation's type
-  /// requires the related trait.
-  fn to_many_relations(&self) -> Any
+    /// Binds the backlinks of the entity to the id of the object, after a put
+    fn attach_backlinks(&mut self, _id: c::obx_id) {}
 }
-*/
 
-// Reference from Store and Box with this type
-pub trait OBBlanket: IdExt + FBOBBridge {}
-impl<T> OBBlanket for T where T: IdExt + FBOBBridge {}
+pub trait UniqueExt: OBBlanket + Sized {
+    /// A condition per unique property that fails on conflict, matching the value
+    /// of this object, by property name. Used by Box::put_checked.
+    /// Optional unique properties are skipped.
+    fn unique_conditions(&self) -> Vec<(&'static str, Condition<Self>)> {
+        Vec::new()
+    }
+}
+
+/// Borrowed views of the entity, generated as `<Entity>Ref<'tx>`,
+/// their accessors read the fields straight from the stored data.
+/// See Box::get_ref and Query::for_each_ref.
+pub trait RefExt: OBBlanket {
+    type Ref<'tx>;
+
+    /// # Safety
+    /// The table must be read from the data of this entity
+    unsafe fn make_ref(table: Table<'_>) -> Self::Ref<'_>;
+}
 
-use flatbuffers::Table;
+/// Maps the type of a `#[property(convert = "MyConverter")]` field to the Stored type,
+/// i64, String or Vec<u8>. The attribute names it as `type = "long"` (the default),
+/// "string" or "bytes", the generated code fails to compile if they don't match.
+/// Conditions on the property take the field type, see query::traits::ConvertedExt.
+pub trait PropertyConverter {
+    type Value;
+    type Stored: StoredValue;
 
-pub trait EntityFactoryExt<T: ?Sized> {
+    fn to_stored(value: &Self::Value) -> Self::Stored;
+    fn from_stored(stored: Self::Stored) -> Self::Value;
+}
+
+// Reference from Store and Box with this type
+pub trait OBBlanket: IdExt + FBOBBridge + RelationExt {}
+impl<T> OBBlanket for T where T: IdExt + FBOBBridge + RelationExt {}
+
+// Shared by the boxes of all threads
+pub trait EntityFactoryExt<T: ?Sized>: Send + Sync {
     fn make(&self, table: &mut Table) -> T;
     fn get_entity_id(&self) -> c::obx_schema_id;
     fn new_entity(&self) -> T;
 }
 pub struct Factory<T> {
-    pub phantom_data: PhantomData<T>,
+    // only the type, so the factory is Send + Sync for any T
+    pub phantom_data: PhantomData<fn() -> T>,
     pub schema_id: c::obx_schema_id,
 }
 
+/// The entity factories of a store, by entity type
+pub type FactoryMap = anymap::Map<dyn anymap::any::Any + Send + Sync>;
+
 pub fn make_from_trait<T>(map: anymap::AnyMap, table: &mut Table) -> Option<T>
 where
     T: 'static,
@@ -84,6 +122,8 @@ fn blanket_directly_applied_on_entity_type() {
         }
     }
 
+    impl RelationExt for SomeEntity {}
+
     // call trait method on original object
     let e0 = SomeEntity { id: 1 };
 
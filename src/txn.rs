@@ -1,17 +1,62 @@
 #![allow(dead_code)]
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use flatbuffers::FlatBufferBuilder;
+
 use crate::c::*;
+use crate::cursor::Cursor;
+use crate::error::Error;
+use crate::traits::{EntityFactoryExt, FactoryMap, OBBlanket};
 use crate::{c, error};
 
-pub(crate) struct Tx {
+const TX_RUNNING: u8 = 0;
+const TX_COMMITTED: u8 = 1;
+const TX_ABORTED: u8 = 2;
+
+/// Whether a write transaction was committed, shared with the
+/// pending relation changes that were applied in it.
+#[derive(Clone)]
+pub struct TxOutcome(Arc<AtomicU8>);
+
+impl Default for TxOutcome {
+    fn default() -> Self {
+        TxOutcome(Arc::new(AtomicU8::new(TX_RUNNING)))
+    }
+}
+
+impl TxOutcome {
+    pub(crate) fn is_committed(&self) -> bool {
+        self.0.load(Ordering::Acquire) == TX_COMMITTED
+    }
+
+    fn set(&self, state: u8) {
+        self.0.store(state, Ordering::Release);
+    }
+}
+
+pub struct Tx {
     // pub(crate) error: Option<Error>,
     pub(crate) obx_txn: *mut OBX_txn,
     pub(crate) ptr_closed: bool,
+    is_mut: bool,
+    is_aborted: bool,
+    pub(crate) outcome: TxOutcome,
 }
 
 impl Drop for Tx {
     fn drop(&mut self) {
         unsafe {
             if !self.ptr_closed && !self.obx_txn.is_null() {
+                // a write tx that was never committed, e.g. on a panic,
+                // is rolled back explicitly before it is closed
+                if self.is_mut && !self.is_aborted {
+                    if let Some(err) = c::call(c::obx_txn_abort(self.obx_txn)).err() {
+                        eprintln!("Error: txn: {err}");
+                    }
+                    self.outcome.set(TX_ABORTED);
+                }
                 match c::call(c::obx_txn_close(self.obx_txn)).err() {
                     Some(err) => eprintln!("Error: txn: {err}"),
                     _ => (),
@@ -29,6 +74,9 @@ impl Tx {
         c::new_mut(unsafe { obx_txn_read(store) }).map(|obx_txn| Tx {
             obx_txn,
             ptr_closed: false,
+            is_mut: false,
+            is_aborted: false,
+            outcome: TxOutcome::default(),
         })
     }
 
@@ -37,30 +85,175 @@ impl Tx {
         c::new_mut(unsafe { obx_txn_write(store) }).map(|obx_txn| Tx {
             obx_txn,
             ptr_closed: false,
+            is_mut: true,
+            is_aborted: false,
+            outcome: TxOutcome::default(),
         })
     }
 
+    pub fn is_write(&self) -> bool {
+        self.is_mut
+    }
+
     // only run on write tx, read tx closes itself on the drop
     pub(crate) fn success(&mut self) -> error::Result<()> {
         let r = unsafe { obx_txn_success(self.obx_txn) };
 
         if r == 0 {
             self.ptr_closed = true;
+            self.outcome.set(TX_COMMITTED);
             return Ok(());
         }
 
         c::call(r)
     }
 
-    fn abort(&mut self) -> error::Result<()> {
+    // the pointer still has to be closed, drop takes care of that
+    pub(crate) fn abort(&mut self) -> error::Result<()> {
+        self.is_aborted = true;
+        self.outcome.set(TX_ABORTED);
         c::call(unsafe { obx_txn_abort(self.obx_txn) })
     }
 
-    // TODO write test
-    pub(crate) fn data_size(&mut self) -> error::Result<(u64, u64)> {
+    pub fn data_size(&self) -> error::Result<(u64, u64)> {
         let mut committed_size = 0;
         let mut size_change = 0;
         c::call(unsafe { obx_txn_data_size(self.obx_txn, &mut committed_size, &mut size_change) })
             .map(|_| (committed_size, size_change))
     }
 }
+
+/// Handed to the closures of Store::write_tx and Store::read_tx,
+/// every box taken from here works on the same transaction.
+pub struct TxScope<'a> {
+    pub(crate) tx: Tx,
+    obx_store: *mut OBX_store,
+    trait_map: &'a FactoryMap,
+    // a read inside a write runs over the same native write transaction
+    nested_in_write: bool,
+}
+
+impl<'a> TxScope<'a> {
+    pub(crate) fn new(tx: Tx, obx_store: *mut OBX_store, trait_map: &'a FactoryMap) -> Self {
+        TxScope {
+            tx,
+            obx_store,
+            trait_map,
+            nested_in_write: false,
+        }
+    }
+
+    pub fn is_write(&self) -> bool {
+        self.tx.is_write()
+    }
+
+    /// Whether this is a read, nested in a write
+    pub fn is_nested_in_write(&self) -> bool {
+        self.nested_in_write
+    }
+
+    pub fn get_box<T: 'static + OBBlanket>(&self) -> error::Result<TxBox<'_, T>> {
+        let helper = if let Some(h) = self.trait_map.get::<Arc<dyn EntityFactoryExt<T>>>() {
+            h
+        } else {
+            Error::new_local("Error: unable to get entity helper").as_result()?
+        };
+        TxBox::new(&self.tx, helper.clone())
+    }
+
+    /// Transactions are reentrant, a read nested in a write
+    /// sees the uncommitted changes of the outer write.
+    pub fn read_tx<R>(&self, f: impl FnOnce(&TxScope) -> error::Result<R>) -> error::Result<R> {
+        let mut scope = TxScope::new(Tx::new(self.obx_store)?, self.obx_store, self.trait_map);
+        scope.nested_in_write = self.is_write() || self.nested_in_write;
+        f(&scope)
+    }
+
+    /// The committed size of the data, and its change in this transaction
+    pub fn data_size(&self) -> error::Result<(u64, u64)> {
+        self.tx.data_size()
+    }
+
+    // commit on Ok, abort on Err, a panic is handled by Tx::drop
+    pub(crate) fn finish<R>(mut self, result: error::Result<R>) -> error::Result<R> {
+        match result {
+            Ok(r) => {
+                self.tx.success()?;
+                Ok(r)
+            }
+            Err(err) => {
+                if let Err(abort_err) = self.tx.abort() {
+                    eprintln!("Error: txn: {abort_err}");
+                }
+                Err(err)
+            }
+        }
+    }
+}
+
+/// A Box that lives as long as the transaction it was taken from,
+/// nothing is committed until the closure of the transaction returns Ok.
+pub struct TxBox<'tx, T: OBBlanket> {
+    cursor: Cursor<T>,
+    builder: FlatBufferBuilder<'tx>,
+    phantom_data: PhantomData<&'tx Tx>,
+}
+
+impl<'tx, T: OBBlanket> TxBox<'tx, T> {
+    fn new(tx: &'tx Tx, helper: Arc<dyn EntityFactoryExt<T>>) -> error::Result<Self> {
+        Cursor::new_in_tx(tx, helper).map(|cursor| TxBox {
+            cursor,
+            builder: FlatBufferBuilder::new(),
+            phantom_data: PhantomData,
+        })
+    }
+
+    pub fn put(&mut self, object: &mut T) -> error::Result<c::obx_id> {
+        self.cursor.put_entity(&mut self.builder, object)
+    }
+
+    pub fn put_many(&mut self, objects: Vec<&mut T>) -> error::Result<Vec<c::obx_id>> {
+        let mut vec_out = Vec::<c::obx_id>::new();
+        for o in objects {
+            vec_out.push(self.put(o)?);
+        }
+        Ok(vec_out)
+    }
+
+    pub fn get(&mut self, id: c::obx_id) -> error::Result<Option<T>> {
+        self.cursor.get_entity(id)
+    }
+
+    pub fn get_many(&mut self, ids: &[c::obx_id]) -> error::Result<Vec<Option<T>>> {
+        let mut r = Vec::<Option<T>>::new();
+        for id in ids {
+            r.push(self.cursor.get_entity(*id)?);
+        }
+        Ok(r)
+    }
+
+    pub fn get_all(&mut self) -> error::Result<Vec<T>> {
+        self.cursor.get_entities()
+    }
+
+    pub fn contains(&mut self, id: c::obx_id) -> error::Result<bool> {
+        self.get(id).map(|o| o.is_some())
+    }
+
+    pub fn count(&mut self) -> error::Result<u64> {
+        self.cursor.count()
+    }
+
+    pub fn is_empty(&mut self) -> error::Result<bool> {
+        self.cursor.is_empty()
+    }
+
+    pub fn remove_with_id(&mut self, id: c::obx_id) -> error::Result<bool> {
+        self.cursor.remove(id)
+    }
+
+    pub fn remove_all(&mut self) -> error::Result<u64> {
+        let count = self.cursor.count()?;
+        self.cursor.remove_all().map(|_| count)
+    }
+}